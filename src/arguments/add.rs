@@ -1,4 +1,7 @@
-use std::io::{self, BufRead};
+use std::{
+    io::{self, BufRead},
+    str::FromStr,
+};
 
 use clap::{Args, value_parser};
 use color_eyre::eyre::{self, ErrReport, Result};
@@ -6,6 +9,12 @@ use color_eyre::eyre::{self, ErrReport, Result};
 use zeroize::Zeroize;
 
 use crate::otp::{
+    algorithms::{
+        blizzard_otp_maker::{BLIZZARD_DIGITS, BLIZZARD_PERIOD, normalize_serial},
+        ocra_maker::OcraSuite,
+        symantec_otp_maker::{SYMANTEC_DIGITS, SYMANTEC_PERIOD, normalize_credential_id},
+    },
+    code_encoder::CodeEncoder,
     from_otp_uri::FromOtpUri,
     otp_algorithm::OTPAlgorithm,
     otp_element::{OTPDatabase, OTPElement, OTPElementBuilder},
@@ -28,7 +37,7 @@ pub struct AddArgs {
     #[arg(short, long, default_value = "")]
     pub issuer: String,
 
-    /// Code label
+    /// Code label, the serial for Battle.net codes and the credential ID for Symantec VIP ones
     #[arg(short, long, required_unless_present = "otp_uri")]
     pub label: Option<String>,
 
@@ -50,7 +59,7 @@ pub struct AddArgs {
     #[arg(short = 'e', long, default_value_t = 30)]
     pub period: u64,
 
-    /// HOTP counter
+    /// HOTP counter, also used by the OCRA suites having one, where it defaults to 0
     #[arg(short, long, required_if_eq("otp_type", "HOTP"))]
     pub counter: Option<u64>,
 
@@ -63,6 +72,19 @@ pub struct AddArgs {
     )]
     pub pin: Option<String>,
 
+    /// OCRA suite, like OCRA-1:HOTP-SHA1-6:QN08, which also sets the algorithm and digits
+    #[arg(long, required_if_eq("otp_type", "ocra"), value_parser = parse_ocra_suite)]
+    pub ocra_suite: Option<String>,
+
+    /// How TOTP and HOTP codes are rendered: decimal, steam or alphabet: followed by the characters,
+    /// the first one being the zero. Alphabet codes are written most significant character first
+    #[arg(long, default_value = "decimal", value_parser = CodeEncoder::from_str)]
+    pub encoder: CodeEncoder,
+
+    /// Tag to group the code, can be repeated
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
     /// Pass the secret through the standard input
     #[arg(long = "secret-stdin", default_value_t = false)]
     take_secret_from_stdin: bool,
@@ -70,7 +92,8 @@ pub struct AddArgs {
 
 impl SubcommandExecutor for AddArgs {
     fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
-        let otp_element = if self.otp_uri {
+        let tags = self.tags.clone();
+        let mut otp_element = if self.otp_uri {
             let mut otp_uri = rpassword::prompt_password("Insert the otp uri: ").unwrap();
             let result = OTPElement::from_otp_uri(otp_uri.as_str());
             otp_uri.zeroize();
@@ -79,6 +102,10 @@ impl SubcommandExecutor for AddArgs {
             get_from_args(self)?
         };
 
+        if otp_element.type_ == OTPType::Blizzard {
+            println!("Restore code: {}", otp_element.blizzard_restore_code()?);
+        }
+        otp_element.add_tags(&tags);
         database.add_element(otp_element);
         Ok(database)
     }
@@ -98,15 +125,52 @@ fn get_from_args(matches: AddArgs) -> color_eyre::Result<OTPElement> {
 }
 
 fn map_args_to_code(secret: String, matches: AddArgs) -> Result<OTPElement> {
+    let ocra_suite = matches
+        .ocra_suite
+        .filter(|_| matches.otp_type == OTPType::Ocra);
+    let label = matches.label.unwrap();
+    // Vendor codes have fixed parameters, stored so they are kept by the exported databases
+    let mut counter = matches.counter;
+    let (label, digits, algorithm, period) = match matches.otp_type {
+        OTPType::Blizzard => (
+            normalize_serial(&label)?,
+            BLIZZARD_DIGITS,
+            OTPAlgorithm::Sha1,
+            BLIZZARD_PERIOD,
+        ),
+        OTPType::Symantec => (
+            normalize_credential_id(&label)?,
+            SYMANTEC_DIGITS,
+            OTPAlgorithm::Sha1,
+            SYMANTEC_PERIOD,
+        ),
+        OTPType::Ocra => {
+            let suite = OcraSuite::from_str(ocra_suite.as_deref().unwrap_or_default())?;
+            // Counter based suites start from the first counter if none is given
+            if suite.counter {
+                counter = counter.or(Some(0));
+            }
+            (label, suite.digits, suite.algorithm, matches.period)
+        }
+        _ => (label, matches.digits, matches.algorithm, matches.period),
+    };
     OTPElementBuilder::default()
         .secret(secret)
         .issuer(matches.issuer)
-        .label(matches.label.unwrap())
-        .digits(matches.digits)
+        .label(label)
+        .digits(digits)
         .type_(matches.otp_type)
-        .algorithm(matches.algorithm)
-        .period(matches.period)
-        .counter(matches.counter)
+        .algorithm(algorithm)
+        .period(period)
+        .counter(counter)
         .pin(matches.pin)
+        .ocra_suite(ocra_suite)
+        .encoder(matches.encoder)
         .build()
 }
+
+fn parse_ocra_suite(s: &str) -> Result<String, String> {
+    OcraSuite::from_str(s)
+        .map(|_| s.to_string())
+        .map_err(|e| e.to_string())
+}
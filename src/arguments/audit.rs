@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+
+use clap::Args;
+use color_eyre::eyre::eyre;
+use data_encoding::BASE32_NOPAD;
+use serde::Serialize;
+
+use crate::{
+    otp::{
+        otp_algorithm::OTPAlgorithm,
+        otp_element::{OTPDatabase, OTPElement},
+        otp_type::OTPType,
+    },
+    utils::{current_timestamp, format_date},
+};
+
+use super::SubcommandExecutor;
+
+/// Secrets shorter than this number of bits are considered weak, as recommended by rfc4226
+const MINIMUM_SECRET_BITS: usize = 128;
+
+#[derive(Args)]
+pub struct AuditArgs {
+    /// Print the report in JSON format
+    #[arg(short, long, default_value_t = false)]
+    pub json: bool,
+
+    /// Also report the codes not copied or printed in the given time, like 180d, 12w or 1y
+    #[arg(long = "unused-since", value_name = "DURATION", value_parser = parse_duration)]
+    pub unused_since: Option<u64>,
+}
+
+#[derive(Serialize, Debug, PartialEq, Eq, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum AuditKind {
+    ShortSecret,
+    ReusedSecret,
+    WeakAlgorithm,
+    NonstandardPeriod,
+    NonstandardDigits,
+    ResetCounter,
+    MissingName,
+    NoRecoveryCodesLeft,
+    Unused,
+}
+
+/// Single problem found in a code, serialized in the --json output
+#[derive(Serialize)]
+struct AuditFinding<'a> {
+    index: usize,
+    issuer: &'a str,
+    label: &'a str,
+    kind: AuditKind,
+    message: String,
+}
+
+impl SubcommandExecutor for AuditArgs {
+    fn run_command(self, database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        let unused_before = self
+            .unused_since
+            .map(|duration| current_timestamp().saturating_sub(duration));
+        let findings = audit(database.elements_ref(), unused_before);
+
+        if self.json {
+            let stringified = serde_json::to_string_pretty(&findings)
+                .map_err(|e| eyre!("Error during JSON serialization: {:?}", e))?;
+            print!("{stringified}");
+        } else if findings.is_empty() {
+            println!("No problems found");
+        } else {
+            findings.iter().for_each(|f| {
+                println!("{} ({}, {}): {}", f.index, f.issuer, f.label, f.message);
+            });
+            println!(
+                "{} problems found in {} codes",
+                findings.len(),
+                database.elements_ref().len()
+            );
+        }
+        Ok(database)
+    }
+}
+
+/// Audits the given codes, reporting the ones not used after the `unused_before` timestamp if given
+fn audit(elements: &[OTPElement], unused_before: Option<u64>) -> Vec<AuditFinding<'_>> {
+    let secrets: Vec<Option<Vec<u8>>> = elements.iter().map(decode_secret).collect();
+
+    // Group the codes sharing the same secret, skipping the undecodable ones
+    let mut reused: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    secrets.iter().enumerate().for_each(|(i, secret)| {
+        if let Some(secret) = secret {
+            reused.entry(secret.as_slice()).or_default().push(i + 1);
+        }
+    });
+
+    let mut findings = vec![];
+    for (i, element) in elements.iter().enumerate() {
+        let mut add = |kind, message| {
+            findings.push(AuditFinding {
+                index: i + 1,
+                issuer: &element.issuer,
+                label: &element.label,
+                kind,
+                message,
+            })
+        };
+
+        if let Some(secret) = &secrets[i] {
+            let bits = secret.len() * 8;
+            if bits < MINIMUM_SECRET_BITS {
+                add(
+                    AuditKind::ShortSecret,
+                    format!(
+                        "Secret is {bits} bits long, at least {MINIMUM_SECRET_BITS} are recommended"
+                    ),
+                );
+            }
+            let others: Vec<String> = reused[secret.as_slice()]
+                .iter()
+                .filter(|&&index| index != i + 1)
+                .map(ToString::to_string)
+                .collect();
+            if !others.is_empty() {
+                add(
+                    AuditKind::ReusedSecret,
+                    format!("Secret is also used by codes {}", others.join(", ")),
+                );
+            }
+        }
+
+        if let Some(message) = weak_algorithm(element, elements) {
+            add(AuditKind::WeakAlgorithm, message);
+        }
+
+        let (period, digits) = standard_values(element.type_);
+        if uses_period(element.type_) && element.period != period {
+            add(
+                AuditKind::NonstandardPeriod,
+                format!("Period of {} seconds instead of {period}", element.period),
+            );
+        }
+        if !digits.contains(&element.digits) {
+            add(
+                AuditKind::NonstandardDigits,
+                format!("{} digits instead of {}", element.digits, digits[0]),
+            );
+        }
+
+        if element.type_ == OTPType::Hotp && element.counter.unwrap_or_default() == 0 {
+            add(
+                AuditKind::ResetCounter,
+                "HOTP counter is 0, it may have been reset".to_string(),
+            );
+        }
+
+        if element.issuer.trim().is_empty() && element.label.trim().is_empty() {
+            add(
+                AuditKind::MissingName,
+                "Both issuer and label are empty".to_string(),
+            );
+        }
+
+        if element.has_exhausted_recovery_codes() {
+            add(
+                AuditKind::NoRecoveryCodesLeft,
+                "All the recovery codes have been used".to_string(),
+            );
+        }
+
+        // Codes without timestamps were added before the usage tracking, so nothing is known about them
+        if let Some(unused_before) = unused_before {
+            match (element.last_used_at, element.created_at) {
+                (Some(last_used), _) if last_used < unused_before => add(
+                    AuditKind::Unused,
+                    format!("Not used since {}", format_date(last_used)),
+                ),
+                (None, Some(created)) if created < unused_before => add(
+                    AuditKind::Unused,
+                    format!("Never used since its creation on {}", format_date(created)),
+                ),
+                _ => {}
+            }
+        }
+    }
+    findings
+}
+
+fn decode_secret(element: &OTPElement) -> Option<Vec<u8>> {
+    match element.type_ {
+        OTPType::Motp => hex::decode(&element.secret).ok(),
+        _ => BASE32_NOPAD
+            .decode(
+                element
+                    .secret
+                    .trim_end_matches('=')
+                    .to_uppercase()
+                    .as_bytes(),
+            )
+            .ok(),
+    }
+}
+
+/// MD5 is always reported, SHA1 only if other codes of the same issuer already use a stronger algorithm
+fn weak_algorithm(element: &OTPElement, elements: &[OTPElement]) -> Option<String> {
+    if !matches!(element.type_, OTPType::Totp | OTPType::Hotp) {
+        return None;
+    }
+    match element.algorithm {
+        OTPAlgorithm::Md5 => Some("MD5 is a broken hash algorithm".to_string()),
+        OTPAlgorithm::Sha1 => elements
+            .iter()
+            .filter(|e| {
+                !element.issuer.is_empty() && e.issuer.eq_ignore_ascii_case(&element.issuer)
+            })
+            .map(|e| e.algorithm)
+            .find(|a| matches!(a, OTPAlgorithm::Sha256 | OTPAlgorithm::Sha512))
+            .map(|a| format!("SHA1 is used, but {} supports {a}", element.issuer)),
+        OTPAlgorithm::Sha256 | OTPAlgorithm::Sha512 => None,
+    }
+}
+
+/// Returns the standard period and the allowed digits of the given code type
+fn standard_values(otp_type: OTPType) -> (u64, &'static [u64]) {
+    match otp_type {
+        OTPType::Totp | OTPType::Hotp | OTPType::Ocra => (30, &[6, 8]),
+        OTPType::Steam => (30, &[5]),
+        OTPType::Blizzard => (30, &[8]),
+        OTPType::Symantec => (30, &[6]),
+        OTPType::Yandex => (30, &[8]),
+        OTPType::Motp => (10, &[6]),
+    }
+}
+
+/// Parses a duration like 180d into seconds. Supported units are hours, days, weeks and years,
+/// days are used if missing.
+fn parse_duration(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (amount, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, "d"),
+    };
+    let seconds = match unit {
+        "h" => 3600,
+        "d" => 86400,
+        "w" => 7 * 86400,
+        "y" => 365 * 86400,
+        _ => return Err(format!("Invalid duration unit {unit}, use h, d, w or y")),
+    };
+    amount
+        .parse::<u64>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(seconds))
+        .ok_or(format!("Invalid duration {s}"))
+}
+
+fn uses_period(otp_type: OTPType) -> bool {
+    matches!(otp_type, OTPType::Totp | OTPType::Yandex | OTPType::Motp)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElementBuilder};
+
+    use super::{AuditKind, audit, parse_duration};
+
+    #[test]
+    fn test_strong_codes_have_no_findings() {
+        // Arrange
+        let elements = vec![
+            OTPElementBuilder::default()
+                .secret("JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")
+                .label("label")
+                .issuer("Issuer")
+                .build()
+                .unwrap(),
+        ];
+
+        // Act
+        let findings = audit(&elements, None);
+
+        // Assert
+        assert!(findings.is_empty());
+    }
+
+    #[test]
+    fn test_short_and_reused_secrets() {
+        // Arrange
+        let first = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("First")
+            .build()
+            .unwrap();
+        let mut second = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("Second")
+            .build()
+            .unwrap();
+        second.secret = "jbswy3dpehpk3pxp".to_string();
+        let elements = vec![first, second];
+
+        // Act
+        let kinds: Vec<AuditKind> = audit(&elements, None).iter().map(|f| f.kind).collect();
+
+        // Assert
+        assert_eq!(
+            vec![
+                AuditKind::ShortSecret,
+                AuditKind::ReusedSecret,
+                AuditKind::ShortSecret,
+                AuditKind::ReusedSecret
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn test_sha1_is_reported_when_issuer_supports_better() {
+        // Arrange
+        let elements = vec![
+            OTPElementBuilder::default()
+                .secret("JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")
+                .label("label")
+                .issuer("Issuer")
+                .build()
+                .unwrap(),
+            OTPElementBuilder::default()
+                .secret("KRSXG5CTMVRXEZLUKRSXG5CTMVRXEZLU")
+                .label("label")
+                .issuer("issuer")
+                .algorithm(OTPAlgorithm::Sha256)
+                .build()
+                .unwrap(),
+        ];
+
+        // Act
+        let findings = audit(&elements, None);
+
+        // Assert
+        assert_eq!(1, findings.len());
+        assert_eq!(AuditKind::WeakAlgorithm, findings[0].kind);
+        assert_eq!(1, findings[0].index);
+    }
+
+    #[test]
+    fn test_nonstandard_values_missing_name_and_recovery_codes() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("")
+            .build()
+            .unwrap();
+        element.label = String::new();
+        element.period = 60;
+        element.digits = 7;
+        element.add_recovery_codes(["abcd-efgh"]);
+        element.use_recovery_code(None).unwrap();
+
+        // Act
+        let kinds: Vec<AuditKind> = audit(&[element], None).iter().map(|f| f.kind).collect();
+
+        // Assert
+        assert_eq!(
+            vec![
+                AuditKind::NonstandardPeriod,
+                AuditKind::NonstandardDigits,
+                AuditKind::MissingName,
+                AuditKind::NoRecoveryCodesLeft
+            ],
+            kinds
+        );
+    }
+
+    #[test]
+    fn test_unused_codes() {
+        // Arrange
+        let mut used = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXPJBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("Used")
+            .build()
+            .unwrap();
+        used.created_at = Some(100);
+        used.last_used_at = Some(1000);
+        let mut unused = OTPElementBuilder::default()
+            .secret("KRSXG5CTMVRXEZLUKRSXG5CTMVRXEZLU")
+            .label("label")
+            .issuer("Unused")
+            .build()
+            .unwrap();
+        unused.created_at = Some(100);
+        let untracked = OTPElementBuilder::default()
+            .secret("MFRGGZDFMZTWQ2LKMFRGGZDFMZTWQ2LK")
+            .label("label")
+            .issuer("Untracked")
+            .build()
+            .unwrap();
+        let elements = [used, unused, untracked];
+
+        // Act
+        let findings = audit(&elements, Some(500));
+
+        // Assert
+        assert_eq!(1, findings.len());
+        assert_eq!(AuditKind::Unused, findings[0].kind);
+        assert_eq!("Unused", findings[0].issuer);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(Ok(180 * 86400), parse_duration("180d"));
+        assert_eq!(Ok(2 * 86400), parse_duration("2"));
+        assert_eq!(Ok(7 * 86400), parse_duration("1w"));
+        assert!(parse_duration("10x").is_err());
+        assert!(parse_duration("d").is_err());
+    }
+}
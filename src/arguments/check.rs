@@ -0,0 +1,58 @@
+use clap::Args;
+use color_eyre::eyre::eyre;
+
+use crate::otp::{history::HistoryAction, otp_element::OTPDatabase};
+
+use super::SubcommandExecutor;
+
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Normalize the secrets and fill the missing values with safe defaults
+    #[arg(short, long, default_value_t = false)]
+    pub fix: bool,
+}
+
+impl SubcommandExecutor for CheckArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        if self.fix {
+            let mut fixed = 0;
+            for i in 0..database.elements_ref().len() {
+                let mut element = database.elements_ref()[i].clone();
+                if element.fix() {
+                    database.record_change(i, HistoryAction::Edit);
+                    if let Some(e) = database.mut_element(i) {
+                        *e = element;
+                    }
+                    fixed += 1;
+                }
+            }
+            if fixed > 0 {
+                database.mark_modified();
+            }
+            println!("Fixed {fixed} codes");
+        }
+
+        let mut invalid = 0;
+        for (index, element) in database.elements_ref().iter().enumerate() {
+            let problems = element.check();
+            if problems.is_empty() {
+                continue;
+            }
+            invalid += 1;
+            println!("{} ({}, {}):", index + 1, element.issuer, element.label);
+            problems
+                .iter()
+                .for_each(|problem| println!("  - {problem}"));
+        }
+
+        if invalid == 0 {
+            println!("No problems found");
+        } else if !self.fix {
+            // With --fix the repaired codes must still be saved, so only report the remaining ones
+            return Err(eyre!(
+                "{invalid} codes have problems, try to repair them using \"cotp check --fix\""
+            ));
+        }
+        Ok(database)
+    }
+}
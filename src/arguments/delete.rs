@@ -10,21 +10,26 @@ use color_eyre::eyre::eyre;
 
 use crate::otp::otp_element::OTPDatabase;
 
-use super::SubcommandExecutor;
+use super::{CodeTarget, SubcommandExecutor};
 
 #[derive(Args)]
+// The code can also be selected by issuer or label
+#[command(mut_arg("index", |a| a.required_unless_present_any(["issuer", "label"])))]
 pub struct DeleteArgs {
-    /// Code Index
-    #[arg(short, long, required_unless_present_any=["issuer", "label"])]
-    pub index: Option<usize>,
+    #[command(flatten)]
+    pub target: CodeTarget,
 
     /// Issuer of the first matching code that will be deleted
-    #[arg(short = 's', long, required_unless_present_any=["index", "label"])]
+    #[arg(short = 's', long, required_unless_present_any=["id", "index", "label"])]
     pub issuer: Option<String>,
 
     /// Label of the first matching code that will be deleted
-    #[arg(short, long, required_unless_present_any=["index","issuer"])]
+    #[arg(short, long, required_unless_present_any=["id", "index","issuer"])]
     pub label: Option<String>,
+
+    /// Delete the code permanently instead of moving it to the trash
+    #[arg(long, default_value_t = false)]
+    pub permanent: bool,
 }
 
 impl SubcommandExecutor for DeleteArgs {
@@ -33,16 +38,31 @@ impl SubcommandExecutor for DeleteArgs {
             return Err(eyre!("There are no elements to delete"));
         }
 
-        let index_to_delete = self
-            .index
-            .and_then(|i| i.checked_sub(1))
-            // Match by issues or label if index filter is missing
-            .or_else(|| get_first_matching_element(&otp_database, &self))
-            .ok_or(eyre!("No code has been found using the given arguments"))?;
+        let index_to_delete = if self.target.id.is_some() || self.target.index.is_some() {
+            self.target.resolve(&otp_database)?
+        } else {
+            // Match by issuer or label if index and id are missing
+            get_first_matching_element(&otp_database, &self)
+                .ok_or(eyre!("No code has been found using the given arguments"))?
+        };
 
         if let Some(element) = otp_database.elements_ref().get(index_to_delete) {
+            if !self.permanent {
+                let message = format!(
+                    "Moved the {}th code ({}, {}) to the trash",
+                    index_to_delete + 1,
+                    element.issuer,
+                    element.label
+                );
+                otp_database
+                    .archive_element(index_to_delete)
+                    .map_err(|e| eyre!(e))?;
+                println!("{message}");
+                return Ok(otp_database);
+            }
+
             print!(
-                "Are you sure you want to delete the {}th code ({}, {}) [Y,N]: ",
+                "Are you sure you want to permanently delete the {}th code ({}, {}) [Y,N]: ",
                 index_to_delete + 1,
                 element.issuer,
                 element.label
@@ -63,7 +83,7 @@ impl SubcommandExecutor for DeleteArgs {
     }
 }
 
-fn read_confirmation_line() -> color_eyre::Result<String> {
+pub(super) fn read_confirmation_line() -> color_eyre::Result<String> {
     let mut output = String::with_capacity(1);
 
     if io::stdin().read_line(&mut output)? > 0 {
@@ -100,6 +120,8 @@ fn get_first_matching_element(
         .elements_ref()
         .iter()
         .enumerate()
+        // Codes in the trash can only be matched to delete them permanently
+        .filter(|(_, element)| delete_args.permanent || !element.is_archived())
         .find(|(_, element)| {
             element
                 .issuer
@@ -1,15 +1,19 @@
 use clap::{Args, value_parser};
 use color_eyre::eyre::eyre;
 
-use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPDatabase};
+use std::str::FromStr;
 
-use super::SubcommandExecutor;
+use crate::otp::{
+    code_encoder::CodeEncoder, history::HistoryAction, otp_algorithm::OTPAlgorithm,
+    otp_element::OTPDatabase,
+};
+
+use super::{CodeTarget, SubcommandExecutor};
 
 #[derive(Args)]
 pub struct EditArgs {
-    /// Code Index
-    #[arg(short, long)]
-    pub index: usize,
+    #[command(flatten)]
+    pub target: CodeTarget,
 
     /// Code issuer
     #[arg(short = 's', long)]
@@ -35,10 +39,47 @@ pub struct EditArgs {
     #[arg(short, long)]
     pub counter: Option<u64>,
 
+    /// Seconds added to the clock for this code, like -30 for a server running late, 0 removes it
+    #[arg(long, allow_hyphen_values = true)]
+    pub time_offset: Option<i64>,
+
+    /// How TOTP and HOTP codes are rendered: decimal, steam or alphabet: followed by the characters,
+    /// the first one being the zero. Alphabet codes are written most significant character first
+    #[arg(long, value_parser = CodeEncoder::from_str)]
+    pub encoder: Option<CodeEncoder>,
+
     /// Yandex / MOTP pin
     #[arg(short, long)]
     pub pin: Option<String>,
 
+    /// Add a tag to the code, can be repeated
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Remove a tag from the code, can be repeated
+    #[arg(long = "untag")]
+    pub untags: Vec<String>,
+
+    /// Code note, an empty value removes it
+    #[arg(long)]
+    pub note: Option<String>,
+
+    /// Website of the service, an empty value removes it
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Set a custom field, in the NAME=VALUE format, can be repeated
+    #[arg(long = "field", value_name = "NAME=VALUE", value_parser = parse_field)]
+    pub fields: Vec<(String, String)>,
+
+    /// Remove a custom field, can be repeated
+    #[arg(long = "remove-field", value_name = "NAME")]
+    pub remove_fields: Vec<String>,
+
+    /// Mark or unmark the code as favorite
+    #[arg(long)]
+    pub favorite: Option<bool>,
+
     /// Change code secret
     #[arg(short = 'k', long = "change-secret")]
     pub change_secret: bool,
@@ -50,47 +91,71 @@ impl SubcommandExecutor for EditArgs {
             .change_secret
             .then(|| rpassword::prompt_password("Insert the secret: ").unwrap());
 
-        // User provides row number from dashboard which is equal to the array index plus one
-        let index = self.index;
-
-        if let Some(real_index) = index.checked_sub(1) {
-            if real_index >= database.elements_ref().len() {
-                return Err(eyre!("{index} is an invalid index"));
-            }
-
-            match database.mut_element(real_index) {
-                Some(element) => {
-                    if let Some(v) = self.issuer {
-                        element.issuer = v;
-                    }
-                    if let Some(v) = self.label {
-                        element.label = v;
-                    }
-                    if let Some(v) = self.digits {
-                        element.digits = v;
-                    }
-                    if let Some(v) = self.period {
-                        element.period = v;
-                    }
-                    if let Some(v) = self.algorithm {
-                        element.algorithm = v;
-                    }
-                    if self.counter.is_some() {
-                        element.counter = self.counter;
-                    }
-                    if self.pin.is_some() {
-                        element.pin = self.pin;
-                    }
-                    if let Some(s) = secret {
-                        element.secret = s;
-                    }
-                    database.mark_modified();
-                }
-                None => return Err(eyre!("No element found at index {index}")),
-            }
-            Ok(database)
-        } else {
-            Err(eyre!("{index} is an invalid index"))
+        let real_index = self.target.resolve(&database)?;
+
+        database.record_change(real_index, HistoryAction::Edit);
+        let element = database
+            .mut_element(real_index)
+            .ok_or(eyre!("No element found at index {}", real_index + 1))?;
+        if let Some(v) = self.issuer {
+            element.issuer = v;
+        }
+        if let Some(v) = self.label {
+            element.label = v;
+        }
+        if let Some(v) = self.digits {
+            element.digits = v;
+        }
+        if let Some(v) = self.period {
+            element.period = v;
+        }
+        if let Some(v) = self.algorithm {
+            element.algorithm = v;
+        }
+        if self.counter.is_some() {
+            element.counter = self.counter;
+        }
+        if let Some(v) = self.time_offset {
+            element.time_offset_seconds = (v != 0).then_some(v);
+        }
+        if let Some(v) = self.encoder {
+            element.encoder = v;
+        }
+        if self.pin.is_some() {
+            element.pin = self.pin;
+        }
+        if let Some(s) = secret {
+            element.secret = s;
+        }
+        element.remove_tags(&self.untags);
+        element.add_tags(&self.tags);
+        if let Some(v) = self.note {
+            element.note = non_empty(v);
+        }
+        if let Some(v) = self.url {
+            element.url = non_empty(v);
+        }
+        self.remove_fields.iter().for_each(|name| {
+            element.fields.remove(name.trim());
+        });
+        element.fields.extend(self.fields);
+        if let Some(v) = self.favorite {
+            element.favorite = v;
+        }
+        database.mark_modified();
+        Ok(database)
+    }
+}
+
+fn non_empty(value: String) -> Option<String> {
+    Some(value).filter(|v| !v.trim().is_empty())
+}
+
+fn parse_field(s: &str) -> Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
         }
+        _ => Err(format!("Invalid field {s}, expected NAME=VALUE")),
     }
 }
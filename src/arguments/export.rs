@@ -20,6 +20,14 @@ pub struct ExportArgs {
     /// Export format
     #[command(flatten)]
     pub format: Option<ExportFormat>,
+
+    /// Encrypt the backup to the given age X25519 recipient, can be repeated
+    #[arg(short, long = "recipient")]
+    pub recipients: Vec<String>,
+
+    /// Only export the codes having this tag, can be repeated
+    #[arg(short, long = "tag")]
+    pub tags: Vec<String>,
 }
 
 #[derive(Args)]
@@ -54,25 +62,31 @@ impl Default for ExportFormat {
 }
 
 impl SubcommandExecutor for ExportArgs {
-    fn run_command(self, database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+    fn run_command(self, otp_database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        let database = otp_database.filter_by_tags(&self.tags);
         let export_format = self.format.unwrap_or_default();
         let exported_path = if self.path.is_dir() {
-            self.path.join("exported.cotp")
+            self.path.join(if self.recipients.is_empty() {
+                "exported.cotp"
+            } else {
+                "exported.cotp.age"
+            })
         } else {
             self.path
         };
+        let recipients = self.recipients.as_slice();
 
         if export_format.cotp {
-            do_export(&database, exported_path)
+            do_export(&database, exported_path, recipients)
         } else if export_format.andotp {
             let andotp: &Vec<OTPElement> = (&database).into();
-            do_export(&andotp, exported_path)
+            do_export(&andotp, exported_path, recipients)
         } else if export_format.otp_uri {
             let otp_uri_list: OtpUriList = (&database).into();
-            do_export(&otp_uri_list, exported_path)
+            do_export(&otp_uri_list, exported_path, recipients)
         } else if export_format.freeotp_plus {
             let freeotp_plus: FreeOTPPlusJson = (&database).try_into()?;
-            do_export(&freeotp_plus, exported_path)
+            do_export(&freeotp_plus, exported_path, recipients)
         } else {
             unreachable!("Unreachable code");
         }
@@ -81,7 +95,7 @@ impl SubcommandExecutor for ExportArgs {
                 "Exported to path: {}",
                 path.to_str().unwrap_or("Failed to encode path")
             );
-            database
+            otp_database
         })
         .map_err(|e| eyre!("An error occurred while exporting database: {e}"))
     }
@@ -3,26 +3,38 @@ use crate::{clipboard, otp::otp_element::OTPElement};
 use clap::Args;
 use color_eyre::eyre::eyre;
 use globset::{GlobBuilder, GlobMatcher};
+use uuid::Uuid;
 
-use super::SubcommandExecutor;
+use super::{CodeTime, SubcommandExecutor};
 
 #[derive(Args, Default)]
 pub struct ExtractArgs {
     /// Code Index
-    #[arg(short, long, required_unless_present_any = ["issuer", "label"])]
+    #[arg(short, long, required_unless_present_any = ["id", "issuer", "label", "tags"])]
     pub index: Option<usize>,
 
+    /// Code id
+    #[arg(long, conflicts_with = "index", required_unless_present_any = ["index", "issuer", "label", "tags"])]
+    pub id: Option<Uuid>,
+
     /// Code issuer, may be a glob pattern
-    #[arg(short = 's', long, required_unless_present_any = ["index", "label"])]
+    #[arg(short = 's', long, required_unless_present_any = ["id", "index", "label", "tags"])]
     pub issuer: Option<String>,
 
     /// Code label, may be a glob pattern
-    #[arg(short, long, required_unless_present_any = ["index", "issuer"])]
+    #[arg(short, long, required_unless_present_any = ["id", "index", "issuer", "tags"])]
     pub label: Option<String>,
 
+    /// Only match the codes having this tag, can be repeated
+    #[arg(short, long = "tag")]
+    pub tags: Vec<String>,
+
     /// Copy the code to the clipboard
     #[arg(short, long = "copy-clipboard", default_value_t = false)]
     pub copy_to_clipboard: bool,
+
+    #[command(flatten)]
+    pub time: CodeTime,
 }
 
 // Contains glob filters for each field we can filter on
@@ -30,6 +42,8 @@ struct ExtractFilterGlob {
     issuer_glob: Option<GlobMatcher>,
     label_glob: Option<GlobMatcher>,
     index: Option<usize>,
+    id: Option<Uuid>,
+    tags: Vec<String>,
 }
 
 impl TryFrom<ExtractArgs> for ExtractFilterGlob {
@@ -52,6 +66,8 @@ impl TryFrom<ExtractArgs> for ExtractFilterGlob {
             issuer_glob,
             label_glob,
             index: value.index,
+            id: value.id,
+            tags: value.tags,
         })
     }
 }
@@ -66,19 +82,25 @@ fn create_matcher(
 }
 
 impl SubcommandExecutor for ExtractArgs {
-    fn run_command(self, otp_database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+    fn run_command(mut self, mut otp_database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
         let copy_to_clipboard = self.copy_to_clipboard;
+        let time = std::mem::take(&mut self.time);
         let globbed: ExtractFilterGlob = self.try_into()?;
 
         let first_with_filters = find_match(&otp_database, globbed);
 
         if let Some(otp) = first_with_filters {
-            let code = otp.get_otp_code()?;
+            let code = otp.get_otp_code_at(time.time_for(otp))?;
+            let id = otp.id;
             println!("{code}");
             if copy_to_clipboard {
                 let _ = clipboard::copy_string_to_clipboard(code.as_str())?;
                 println!("Copied to clipboard");
             }
+            // Codes generated for another time are not counted as used
+            if let Some(index) = otp_database.find_index_by_id(&id).filter(|_| time.is_now()) {
+                otp_database.record_use(index);
+            }
             Ok(otp_database)
         } else {
             Err(eyre!("No such code found with these fields"))
@@ -88,9 +110,10 @@ impl SubcommandExecutor for ExtractArgs {
 
 fn find_match(otp_database: &OTPDatabase, globbed: ExtractFilterGlob) -> Option<&OTPElement> {
     otp_database
-        .elements
+        .elements_ref()
         .iter()
         .enumerate()
+        .filter(|(_, code)| !code.is_archived())
         .find(|(index, code)| filter_extract(&globbed, *index, code))
         .map(|(_, code)| code)
 }
@@ -98,6 +121,8 @@ fn find_match(otp_database: &OTPDatabase, globbed: ExtractFilterGlob) -> Option<
 fn filter_extract(args: &ExtractFilterGlob, index: usize, candidate: &OTPElement) -> bool {
     let match_by_index = args.index.is_none_or(|i| i == index);
 
+    let match_by_id = args.id.is_none_or(|id| id == candidate.id);
+
     let match_by_issuer = args
         .issuer_glob
         .as_ref()
@@ -108,7 +133,11 @@ fn filter_extract(args: &ExtractFilterGlob, index: usize, candidate: &OTPElement
         .as_ref()
         .is_none_or(|label| label.is_match(&candidate.label));
 
-    match_by_index && match_by_issuer && match_by_label
+    match_by_index
+        && match_by_id
+        && candidate.has_tags(&args.tags)
+        && match_by_issuer
+        && match_by_label
 }
 
 #[cfg(test)]
@@ -326,4 +355,38 @@ mod tests {
         // Assert
         assert!(found_match.is_some());
     }
+
+    #[test]
+    fn test_filtering_by_id() {
+        // Arrange
+        let mut otp_database = OTPDatabase::default();
+        otp_database.add_element(
+            OTPElementBuilder::default()
+                .issuer("test-issuer")
+                .label("test-label")
+                .secret("AA")
+                .build()
+                .unwrap(),
+        );
+        otp_database.add_element(
+            OTPElementBuilder::default()
+                .issuer("test-issuer2")
+                .label("test-label2")
+                .secret("AA")
+                .build()
+                .unwrap(),
+        );
+        let id = otp_database.elements_ref()[1].id;
+
+        let filter = ExtractArgs {
+            id: Some(id),
+            ..Default::default()
+        };
+
+        // Act
+        let found_match = find_match(&otp_database, filter.try_into().unwrap());
+
+        // Assert
+        assert_eq!("test-issuer2", found_match.unwrap().issuer);
+    }
 }
@@ -0,0 +1,108 @@
+use clap::{Args, value_parser};
+use color_eyre::eyre::eyre;
+use data_encoding::BASE32_NOPAD;
+use zeroize::Zeroize;
+
+use crate::otp::{
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{OTPDatabase, OTPElement, OTPElementBuilder},
+};
+
+use super::SubcommandExecutor;
+
+#[derive(Args)]
+pub struct GenerateArgs {
+    /// Code issuer
+    #[arg(short, long, default_value = "")]
+    pub issuer: String,
+
+    /// Code label
+    #[arg(short, long)]
+    pub label: String,
+
+    /// Secret length in bits, a multiple of 8
+    #[arg(short, long, default_value_t = 160, value_parser = value_parser!(u64).range(128..=512))]
+    pub bits: u64,
+
+    /// OTP Algorithm
+    #[arg(short, long, value_enum, default_value_t = OTPAlgorithm::Sha1)]
+    pub algorithm: OTPAlgorithm,
+
+    /// Code digits
+    #[arg(short, long, default_value_t = 6, value_parser = value_parser!(u64).range(1..=10))]
+    pub digits: u64,
+
+    /// Code period
+    #[arg(short = 'e', long, default_value_t = 30)]
+    pub period: u64,
+
+    /// Also add the generated code to the database
+    #[arg(long, default_value_t = false)]
+    pub add: bool,
+}
+
+impl GenerateArgs {
+    /// Generates the code and prints its enrollment URI and QR code, without unlocking the database
+    pub fn run(&self) -> color_eyre::Result<()> {
+        self.generate().map(|element| print_enrollment(&element))
+    }
+
+    fn generate(&self) -> color_eyre::Result<OTPElement> {
+        OTPElementBuilder::default()
+            .secret(generate_secret(self.bits)?)
+            .issuer(self.issuer.as_str())
+            .label(self.label.as_str())
+            .digits(self.digits)
+            .algorithm(self.algorithm)
+            .period(self.period)
+            .build()
+            .map_err(|e| eyre!(e))
+    }
+}
+
+impl SubcommandExecutor for GenerateArgs {
+    fn run_command(self, mut otp_database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        // Codes which are not added are handled by standalone_parser
+        let element = self.generate()?;
+        print_enrollment(&element);
+        otp_database.add_element(element);
+        Ok(otp_database)
+    }
+}
+
+fn print_enrollment(element: &OTPElement) {
+    println!("{}", element.get_otpauth_uri());
+    println!("{}", element.get_qrcode());
+}
+
+/// Returns a random base32 secret of the given number of bits
+fn generate_secret(bits: u64) -> color_eyre::Result<String> {
+    if !bits.is_multiple_of(8) {
+        return Err(eyre!("The secret length must be a multiple of 8 bits"));
+    }
+    let mut bytes = vec![0_u8; (bits / 8) as usize];
+    getrandom::fill(&mut bytes).map_err(|e| eyre!(e))?;
+    let secret = BASE32_NOPAD.encode(&bytes);
+    bytes.zeroize();
+    Ok(secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use data_encoding::BASE32_NOPAD;
+
+    use super::generate_secret;
+
+    #[test]
+    fn test_generated_secrets() {
+        // Act
+        let first = generate_secret(160).unwrap();
+        let second = generate_secret(160).unwrap();
+
+        // Assert
+        assert_eq!(32, first.len());
+        assert_eq!(20, BASE32_NOPAD.decode(first.as_bytes()).unwrap().len());
+        assert_ne!(first, second);
+        assert!(generate_secret(130).is_err());
+    }
+}
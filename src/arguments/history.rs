@@ -0,0 +1,30 @@
+use clap::Args;
+
+use crate::{otp::otp_element::OTPDatabase, utils::format_date_time};
+
+use super::SubcommandExecutor;
+
+#[derive(Args)]
+pub struct HistoryArgs {}
+
+impl SubcommandExecutor for HistoryArgs {
+    fn run_command(self, database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        if database.history().is_empty() {
+            println!("No changes recorded");
+        }
+        // Number the changes like "cotp undo" counts them, starting from the most recent
+        database
+            .history()
+            .iter()
+            .rev()
+            .enumerate()
+            .for_each(|(i, entry)| {
+                println!(
+                    "{0: <4} {1}  {entry}",
+                    i + 1,
+                    format_date_time(entry.timestamp)
+                );
+            });
+        Ok(database)
+    }
+}
@@ -6,12 +6,16 @@ use color_eyre::eyre::eyre;
 use crate::{
     exporters::otp_uri::OtpUriList,
     importers::{
-        aegis::AegisJson, aegis_encrypted::AegisEncryptedDatabase,
-        authy_remote_debug::AuthyExportedList, converted::ConvertedJsonList,
-        freeotp_plus::FreeOTPPlusJson, google_authenticator::import_from_google_authenticator,
-        importer::import_from_path,
+        aegis::AegisJson,
+        aegis_encrypted::AegisEncryptedDatabase,
+        authy_remote_debug::AuthyExportedList,
+        converted::ConvertedJsonList,
+        freeotp_plus::FreeOTPPlusJson,
+        google_authenticator::import_from_string,
+        importer::{convert_backup, deserialize_backup, import_from_str, read_backup},
     },
     otp::otp_element::{OTPDatabase, OTPElement},
+    password::PasswordSource,
 };
 
 use super::SubcommandExecutor;
@@ -24,6 +28,21 @@ pub struct ImportArgs {
     /// Backup file path
     #[arg(short, long)]
     pub path: PathBuf,
+
+    /// Decrypt an age encrypted backup using the given identity file
+    #[arg(long)]
+    pub identity: Option<PathBuf>,
+
+    /// Read the backup password from the first line of the given file
+    #[arg(long = "backup-password-file")]
+    pub backup_password_file: Option<PathBuf>,
+
+    /// Run the given shell command and read the backup password from its output
+    #[arg(
+        long = "backup-password-command",
+        conflicts_with = "backup_password_file"
+    )]
+    pub backup_password_command: Option<String>,
 }
 
 #[derive(Args)]
@@ -77,28 +96,36 @@ pub struct BackupType {
 
 impl SubcommandExecutor for ImportArgs {
     fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
-        let path = self.path;
+        let content = read_backup(&self.path, self.identity.as_deref())?;
 
         let backup_type = self.backup_type;
 
         let result = if backup_type.cotp {
-            import_from_path::<OTPDatabase>(path)
+            import_from_str::<OTPDatabase>(&content)
         } else if backup_type.andotp {
-            import_from_path::<Vec<OTPElement>>(path)
+            import_from_str::<Vec<OTPElement>>(&content)
         } else if backup_type.aegis {
-            import_from_path::<AegisJson>(path)
+            import_from_str::<AegisJson>(&content)
         } else if backup_type.aegis_encrypted {
-            import_from_path::<AegisEncryptedDatabase>(path)
+            deserialize_backup::<AegisEncryptedDatabase>(&content).and_then(|mut backup| {
+                let password = PasswordSource::from_options(
+                    self.backup_password_file,
+                    self.backup_password_command,
+                )
+                .read("Insert your Aegis password: ", 0)?;
+                backup.set_password(password);
+                convert_backup(backup)
+            })
         } else if backup_type.freeotp_plus {
-            import_from_path::<FreeOTPPlusJson>(path)
+            import_from_str::<FreeOTPPlusJson>(&content)
         } else if backup_type.authy_exported {
-            import_from_path::<AuthyExportedList>(path)
+            import_from_str::<AuthyExportedList>(&content)
         } else if backup_type.google_authenticator {
-            import_from_google_authenticator(path)
+            import_from_string(&content)
         } else if backup_type.authy || backup_type.microsoft_authenticator || backup_type.freeotp {
-            import_from_path::<ConvertedJsonList>(path)
+            import_from_str::<ConvertedJsonList>(&content)
         } else if backup_type.otp_uri {
-            import_from_path::<OtpUriList>(path)
+            import_from_str::<OtpUriList>(&content)
         } else {
             return Err(eyre!("Invalid arguments provided"));
         };
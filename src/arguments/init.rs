@@ -0,0 +1,75 @@
+use std::{fs, path::PathBuf};
+
+use clap::Args;
+use color_eyre::eyre::eyre;
+use zeroize::Zeroize;
+
+use crate::{
+    create_database,
+    crypto::encrypted_database::KdfParams,
+    password::PasswordSource,
+    path::{set_database_path, vault_path},
+};
+
+use super::{CotpArgs, passwd::KdfArgs};
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Read the password from the first line of the standard input
+    #[arg(long = "password-stdin", default_value_t = false)]
+    pub password_stdin: bool,
+
+    /// Read the password from the first line of the given file
+    #[arg(long = "password-file", conflicts_with = "password_stdin")]
+    pub password_file: Option<PathBuf>,
+
+    /// Run the given shell command and read the password from its output
+    #[arg(long = "password-command", conflicts_with_all = ["password_stdin", "password_file"])]
+    pub password_command: Option<String>,
+
+    /// Overwrite the database if it already exists, deleting all its codes
+    #[arg(long, default_value_t = false)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub kdf: KdfArgs,
+}
+
+impl InitArgs {
+    pub fn run(&self, cotp_args: &CotpArgs) -> color_eyre::Result<()> {
+        // Unlike the other subcommands, the vault to create does not exist yet
+        let path = match (&cotp_args.database_path, &cotp_args.vault) {
+            (None, Some(vault)) => vault_path(vault)?,
+            _ => cotp_args.init_path()?,
+        };
+        set_database_path(path.clone());
+        if path.exists() && !self.force {
+            return Err(eyre!(
+                "A database already exists in {}, use --force to overwrite it",
+                path.display()
+            ));
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Options given to this subcommand take precedence over the global ones
+        let password_source = if self.password_stdin {
+            PasswordSource::Stdin
+        } else {
+            match PasswordSource::from_options(
+                self.password_file.clone(),
+                self.password_command.clone(),
+            ) {
+                PasswordSource::Prompt => cotp_args.password_source()?,
+                source => source,
+            }
+        };
+
+        let (_, mut key, _) =
+            create_database(&password_source, self.kdf.apply(KdfParams::default()))?;
+        key.zeroize();
+        println!("Database created in {}", path.display());
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use std::time::Duration;
+
+use clap::{Args, value_parser};
+
+use crate::crypto::cryptography::{benchmark_kdf, suggest_kdf_params};
+
+#[derive(Args)]
+pub struct KdfBenchmarkArgs {
+    /// Desired key derivation time in milliseconds
+    #[arg(short, long, default_value_t = 1000, value_parser = value_parser!(u64).range(100..))]
+    pub target: u64,
+
+    /// Argon2 degree of parallelism
+    #[arg(long = "kdf-parallelism", default_value_t = 4, value_parser = value_parser!(u32).range(1..=64))]
+    pub parallelism: u32,
+}
+
+impl KdfBenchmarkArgs {
+    pub fn run(&self) -> color_eyre::Result<()> {
+        println!("Benchmarking key derivation, this may take a few seconds...");
+        let params = suggest_kdf_params(Duration::from_millis(self.target), self.parallelism)?;
+        let elapsed = benchmark_kdf(&params)?;
+        let memory = params.mem_cost / 1024;
+
+        println!("Suggested key derivation parameters:");
+        println!("  Memory:      {memory} MiB");
+        println!("  Iterations:  {}", params.time_cost);
+        println!("  Parallelism: {}", params.lanes);
+        println!("  Measured:    {} ms", elapsed.as_millis());
+        println!(
+            "Apply them with: cotp passwd --kdf-memory {memory} --kdf-time {} --kdf-parallelism {}",
+            params.time_cost, params.lanes
+        );
+        Ok(())
+    }
+}
@@ -1,16 +1,32 @@
 use clap::Args;
 use color_eyre::eyre::{Result, eyre};
 use serde::Serialize;
+use uuid::Uuid;
 
-use crate::otp::otp_element::{OTPDatabase, OTPElement};
+use crate::otp::{
+    otp_element::{OTPDatabase, OTPElement},
+    otp_type::OTPType,
+    sort_order::{SortOrder, sorted_indexes},
+};
 
-use super::SubcommandExecutor;
+use super::{CodeTime, SubcommandExecutor};
 
 #[derive(Args)]
 pub struct ListArgs {
     /// List output format
     #[command(flatten)]
     pub format: Option<ListFormat>,
+
+    /// Only list the codes having this tag, can be repeated
+    #[arg(short, long = "tag")]
+    pub tags: Vec<String>,
+
+    /// Order of the listed codes, the printed indexes are still their storage positions
+    #[arg(short, long, value_enum, default_value_t = SortOrder::Custom)]
+    pub sort: SortOrder,
+
+    #[command(flatten)]
+    pub time: CodeTime,
 }
 
 /// Defines the output formats of the list subcommand
@@ -38,19 +54,25 @@ impl Default for ListFormat {
 /// Defines JSON structure to output using the --json argument in the list subcommand
 #[derive(Serialize)]
 struct JsonOtpList<'a> {
+    id: &'a Uuid,
     issuer: &'a str,
     label: &'a str,
-    otp_code: String,
+    tags: &'a [String],
+    /// None for challenge-response codes, which have no code without a challenge
+    otp_code: Option<String>,
 }
 
-impl<'a> TryFrom<&'a OTPElement> for JsonOtpList<'a> {
-    type Error = color_eyre::eyre::Error;
-
-    fn try_from(value: &'a OTPElement) -> Result<Self, Self::Error> {
-        let otp_code = value.get_otp_code()?;
+impl<'a> JsonOtpList<'a> {
+    fn new(value: &'a OTPElement, time: u64) -> Result<Self> {
+        let otp_code = match value.type_ {
+            OTPType::Ocra => None,
+            _ => Some(value.get_otp_code_at(time)?),
+        };
         Ok(JsonOtpList {
+            id: &value.id,
             issuer: &value.issuer,
             label: &value.label,
+            tags: &value.tags,
             otp_code,
         })
     }
@@ -60,22 +82,29 @@ const NO_ISSUER_TEXT: &str = "<No issuer>";
 
 impl SubcommandExecutor for ListArgs {
     fn run_command(self, otp_database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        // Keep the original positions, so the printed indexes can be used by other subcommands
+        let elements: Vec<(usize, &OTPElement)> =
+            sorted_indexes(otp_database.elements_ref(), self.sort, false)
+                .into_iter()
+                .map(|i| (i, &otp_database.elements_ref()[i]))
+                .filter(|(_, e)| !e.is_archived() && e.has_tags(&self.tags))
+                .collect();
+
         if self.format.unwrap_or_default().json {
-            let json_elements = otp_database
-                .elements
+            let json_elements = elements
                 .iter()
-                .map(TryInto::try_into)
+                .map(|(_, e)| JsonOtpList::new(e, self.time.time_for(e)))
                 .collect::<Result<Vec<JsonOtpList>>>()?;
 
             let stringified = serde_json::to_string_pretty(&json_elements)
                 .map_err(|e| eyre!("Error during JSON serialization: {:?}", e))?;
             print!("{stringified}");
         } else {
-            if otp_database.elements.is_empty() {
+            if elements.is_empty() {
                 println!("No elements to list");
                 return Ok(otp_database);
             }
-            let issuer_width = calculate_width(&otp_database, |element| {
+            let issuer_width = calculate_width(&elements, |element| {
                 let issuer_length = element.issuer.chars().count();
                 if issuer_length > 0 {
                     issuer_length
@@ -84,52 +113,63 @@ impl SubcommandExecutor for ListArgs {
                 }
             });
 
-            let label_width =
-                calculate_width(&otp_database, |element| element.label.chars().count());
+            let label_width = calculate_width(&elements, |element| element.label.chars().count());
 
+            if !elements.is_sorted_by_key(|(index, _)| *index) {
+                println!(
+                    "Indexes are the storage positions used by the other subcommands, not the listed order"
+                );
+            }
             println!(
                 "{0: <6} {1} {2} {3: <10}",
                 "Index",
-                "Issuer".to_owned() + " ".repeat(issuer_width - 6).as_ref(),
-                "Label".to_owned() + " ".repeat(label_width - 5).as_ref(),
+                "Issuer".to_owned() + " ".repeat(issuer_width.saturating_sub(6)).as_ref(),
+                "Label".to_owned() + " ".repeat(label_width.saturating_sub(5)).as_ref(),
                 "OTP",
             );
-            otp_database
-                .elements
+            elements.iter().for_each(|(index, e)| {
+                println!(
+                    "{0: <6} {1} {2} {3: <10}",
+                    index + 1,
+                    if e.issuer.is_empty() {
+                        NO_ISSUER_TEXT.to_owned()
+                            + " "
+                                .repeat(issuer_width - NO_ISSUER_TEXT.chars().count())
+                                .as_str()
+                    } else {
+                        e.issuer.clone()
+                            + " ".repeat(issuer_width - e.issuer.chars().count()).as_str()
+                    },
+                    e.label.clone() + " ".repeat(label_width - e.label.chars().count()).as_str(),
+                    e.get_otp_code_at(self.time.time_for(e))
+                        .unwrap_or("ERROR".to_string())
+                );
+            });
+
+            let exhausted: Vec<String> = elements
                 .iter()
-                .enumerate()
-                .for_each(|(index, e)| {
-                    println!(
-                        "{0: <6} {1} {2} {3: <10}",
-                        index + 1,
-                        if e.issuer.is_empty() {
-                            NO_ISSUER_TEXT.to_owned()
-                                + " "
-                                    .repeat(issuer_width - NO_ISSUER_TEXT.chars().count())
-                                    .as_str()
-                        } else {
-                            e.issuer.clone()
-                                + " ".repeat(issuer_width - e.issuer.chars().count()).as_str()
-                        },
-                        e.label.clone()
-                            + " ".repeat(label_width - e.label.chars().count()).as_str(),
-                        e.get_otp_code().unwrap_or("ERROR".to_string())
-                    );
-                });
+                .filter(|(_, e)| e.has_exhausted_recovery_codes())
+                .map(|(index, _)| (index + 1).to_string())
+                .collect();
+            if !exhausted.is_empty() {
+                eprintln!(
+                    "\nWarning: no recovery codes left for codes {}",
+                    exhausted.join(", ")
+                );
+            }
         }
 
         Ok(otp_database)
     }
 }
 
-fn calculate_width<F>(otp_database: &OTPDatabase, get_number_of_chars: F) -> usize
+fn calculate_width<F>(elements: &[(usize, &OTPElement)], get_number_of_chars: F) -> usize
 where
     F: Fn(&OTPElement) -> usize,
 {
-    otp_database
-        .elements
+    elements
         .iter()
-        .map(get_number_of_chars)
+        .map(|(_, e)| get_number_of_chars(e))
         .max()
         .unwrap_or_default()
         + 3
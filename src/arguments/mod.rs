@@ -1,23 +1,45 @@
-use crate::otp::otp_element::OTPDatabase;
+use std::path::PathBuf;
+
+use crate::otp::otp_element::{OTPDatabase, OTPElement};
+use crate::password::PasswordSource;
+use crate::path::select_database_path;
+use crate::utils::{current_timestamp, parse_timestamp};
 use crate::{arguments::extract::ExtractArgs, dashboard};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use color_eyre::eyre::eyre;
 use delete::DeleteArgs;
 use enum_dispatch::enum_dispatch;
+use uuid::Uuid;
 
 use self::{
-    add::AddArgs, edit::EditArgs, export::ExportArgs, import::ImportArgs, list::ListArgs,
-    passwd::PasswdArgs,
+    add::AddArgs, audit::AuditArgs, check::CheckArgs, edit::EditArgs, export::ExportArgs,
+    generate::GenerateArgs, history::HistoryArgs, import::ImportArgs, init::InitArgs,
+    kdf_benchmark::KdfBenchmarkArgs, list::ListArgs, ocra::OcraArgs, passwd::PasswdArgs,
+    recipient::RecipientArgs, recovery::RecoveryArgs, trash::TrashArgs, undo::UndoArgs,
+    vault::VaultArgs, verify::VerifyArgs,
 };
 
 mod add;
+mod audit;
+mod check;
 mod delete;
 mod edit;
 mod export;
 mod extract;
+mod generate;
+mod history;
 mod import;
+mod init;
+mod kdf_benchmark;
 mod list;
+mod ocra;
 mod passwd;
+mod recipient;
+mod recovery;
+mod trash;
+mod undo;
+mod vault;
+mod verify;
 
 /// Common trait the all the Subcommands must implement to define the command logic
 #[enum_dispatch]
@@ -30,40 +52,210 @@ pub trait SubcommandExecutor {
 #[command(author, version = env!("COTP_VERSION"), about, long_about = None)]
 pub struct CotpArgs {
     #[command(subcommand)]
-    command: Option<CotpSubcommands>,
+    pub command: Option<CotpSubcommands>,
     /// Fetch the password from standard input
     #[arg(long = "password-stdin", default_value_t = false)]
     pub password_from_stdin: bool,
+    /// Read the password from the first line of the given file
+    #[arg(long = "password-file", conflicts_with = "password_from_stdin")]
+    pub password_file: Option<PathBuf>,
+    /// Run the given shell command and read the password from its output
+    #[arg(long = "password-command", conflicts_with_all = ["password_from_stdin", "password_file"])]
+    pub password_command: Option<String>,
     /// Set the database path
     #[arg(short = 'd', long = "database-path")]
     pub database_path: Option<String>,
+    /// Open the given named vault
+    #[arg(short = 'v', long = "vault", conflicts_with = "database_path")]
+    pub vault: Option<String>,
+    /// Unlock the database using an age identity file instead of the password
+    #[arg(long = "identity", conflicts_with_all = ["password_from_stdin", "password_file", "password_command"])]
+    pub identity: Option<PathBuf>,
+}
+
+impl CotpArgs {
+    /// Opens the given vault interactively on the next initialization
+    pub fn switch_vault(&mut self, vault: String) {
+        self.vault = Some(vault);
+        self.database_path = None;
+        self.password_from_stdin = false;
+        self.password_file = None;
+        self.password_command = None;
+        self.identity = None;
+    }
+
+    /// Initialize singleton database path from the command line options
+    pub fn init_path(&self) -> color_eyre::Result<PathBuf> {
+        select_database_path(self.database_path.as_deref(), self.vault.as_deref())
+    }
+
+    /// Returns where the database password should be read from.
+    /// Command line options take precedence over the `COTP_PASSWORD_FD` environment variable.
+    pub fn password_source(&self) -> color_eyre::Result<PasswordSource> {
+        if self.password_from_stdin {
+            return Ok(PasswordSource::Stdin);
+        }
+        match PasswordSource::from_options(
+            self.password_file.clone(),
+            self.password_command.clone(),
+        ) {
+            PasswordSource::Prompt => Ok(PasswordSource::from_env()?.unwrap_or_default()),
+            source => Ok(source),
+        }
+    }
+}
+
+/// Selects a single code by its index or id
+#[derive(Args)]
+pub struct CodeTarget {
+    /// Code Index
+    #[arg(short, long, required_unless_present = "id", conflicts_with = "id")]
+    pub index: Option<usize>,
+
+    /// Code id
+    #[arg(long)]
+    pub id: Option<Uuid>,
+}
+
+impl CodeTarget {
+    /// Returns the position in the database of the selected code
+    pub fn resolve(&self, database: &OTPDatabase) -> color_eyre::Result<usize> {
+        match (self.id, self.index) {
+            (Some(id), _) => database
+                .find_index_by_id(&id)
+                .ok_or(eyre!("No element found with id {id}")),
+            // User provides row number from dashboard which is equal to the array index plus one
+            (None, Some(index)) => index
+                .checked_sub(1)
+                .filter(|i| *i < database.elements_ref().len())
+                .ok_or(eyre!("{index} is an invalid index")),
+            (None, None) => Err(eyre!("Missing code index or id")),
+        }
+    }
+}
+
+/// Selects the time the codes are generated for
+#[derive(Args, Default)]
+pub struct CodeTime {
+    /// Generate the codes for the given Unix timestamp or RFC 3339 date instead of now
+    #[arg(long, value_parser = parse_timestamp)]
+    pub at: Option<u64>,
+
+    /// Move by the given number of time steps, like -1 for the previous codes
+    #[arg(long, allow_hyphen_values = true, default_value_t = 0)]
+    pub offset: i64,
+}
+
+impl CodeTime {
+    /// Returns true if the codes are generated for the current time
+    pub fn is_now(&self) -> bool {
+        self.at.is_none() && self.offset == 0
+    }
+
+    /// Returns the Unix timestamp the given code must be generated for
+    pub fn time_for(&self, element: &OTPElement) -> u64 {
+        let time = self.at.unwrap_or_else(current_timestamp);
+        let Some(step) = element.time_step() else {
+            return time;
+        };
+        let shift = self.offset.saturating_mul(step as i64);
+        time.saturating_add_signed(shift)
+    }
 }
 
 /// Define available Subcommands
 #[derive(Subcommand)]
-#[enum_dispatch(SubcommandExecutor)]
 pub enum CotpSubcommands {
+    #[command(flatten)]
+    Standalone(StandaloneSubcommands),
+    #[command(flatten)]
+    Database(Box<DatabaseSubcommands>),
+}
+
+/// Subcommands which run without unlocking the database
+#[derive(Subcommand)]
+pub enum StandaloneSubcommands {
+    /// Create a new empty database
+    Init(InitArgs),
+    /// Suggest key derivation parameters for this machine
+    KdfBenchmark(KdfBenchmarkArgs),
+    /// Manage named vaults
+    Vault(VaultArgs),
+}
+
+impl StandaloneSubcommands {
+    pub fn run(&self, cotp_args: &CotpArgs) -> color_eyre::Result<()> {
+        match self {
+            StandaloneSubcommands::Init(args) => args.run(cotp_args),
+            StandaloneSubcommands::KdfBenchmark(args) => args.run(),
+            StandaloneSubcommands::Vault(args) => args.run(cotp_args),
+        }
+    }
+}
+
+/// Subcommands which work on the unlocked database
+#[derive(Subcommand)]
+#[enum_dispatch(SubcommandExecutor)]
+pub enum DatabaseSubcommands {
     /// Add new OTP code
     Add(AddArgs),
     /// Edit an existing OTP Code
     Edit(EditArgs),
     /// List codes
     List(ListArgs),
-    /// Delete codes
+    /// Move codes to the trash, or delete them permanently
     Delete(DeleteArgs),
+    /// Manage the codes in the trash
+    Trash(TrashArgs),
     /// Import codes from other apps
     Import(ImportArgs),
     /// Export cotp database
     Export(ExportArgs),
     /// Copies the selected code into the clipboard, supports glob matching
     Extract(ExtractArgs),
-    /// Change database password
+    /// Create a code with a random secret and print its enrollment URI and QR code
+    Generate(GenerateArgs),
+    /// Check a code against the nearby time steps or the next HOTP counters
+    Verify(VerifyArgs),
+    /// Compute the response of an OCRA code to a challenge
+    Ocra(OcraArgs),
+    /// Check the database for codes which cannot be generated
+    Check(CheckArgs),
+    /// Report weak or nonstandard codes
+    Audit(AuditArgs),
+    /// Manage the backup recovery codes of a code
+    Recovery(RecoveryArgs),
+    /// List the most recent changes to the codes
+    History(HistoryArgs),
+    /// Revert the most recent changes to the codes
+    Undo(UndoArgs),
+    /// Change database password and key derivation parameters
     Passwd(PasswdArgs),
+    /// Manage the age recipients which can unlock the database without the password
+    Recipient(RecipientArgs),
+}
+
+/// Runs the subcommands which do not need to unlock the database.
+/// Returns None if the given subcommand needs it.
+pub fn standalone_parser(matches: &CotpArgs) -> Option<color_eyre::Result<()>> {
+    match &matches.command {
+        Some(CotpSubcommands::Standalone(command)) => Some(command.run(matches)),
+        Some(CotpSubcommands::Database(command)) => match command.as_ref() {
+            DatabaseSubcommands::Generate(args) if !args.add => Some(args.run()),
+            _ => None,
+        },
+        None => None,
+    }
 }
 
-pub fn args_parser(matches: CotpArgs, read_result: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
-    if let Some(command) = matches.command {
-        command.run_command(read_result)
+/// Runs the given subcommand or the dashboard if missing.
+/// Also returns the vault to switch to, if the user requested it from the dashboard.
+pub fn args_parser(
+    command: Option<DatabaseSubcommands>,
+    read_result: OTPDatabase,
+) -> color_eyre::Result<(OTPDatabase, Option<String>)> {
+    if let Some(command) = command {
+        command.run_command(read_result).map(|d| (d, None))
     } else {
         dashboard(read_result).map_err(|e| eyre!("An error occurred: {e}"))
     }
@@ -0,0 +1,52 @@
+use clap::Args;
+use color_eyre::eyre::eyre;
+
+use crate::{
+    otp::{history::HistoryAction, otp_element::OTPDatabase, otp_type::OTPType},
+    utils::current_timestamp,
+};
+
+use super::{CodeTarget, SubcommandExecutor};
+
+#[derive(Args)]
+pub struct OcraArgs {
+    #[command(flatten)]
+    pub target: CodeTarget,
+
+    /// Challenge received from the server
+    #[arg(short, long)]
+    pub challenge: String,
+
+    /// Session information, hex encoded, if required by the suite
+    #[arg(short, long)]
+    pub session: Option<String>,
+}
+
+impl SubcommandExecutor for OcraArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        let index = self.target.resolve(&database)?;
+        let element = &database.elements_ref()[index];
+        if element.type_ != OTPType::Ocra {
+            return Err(eyre!("Code {} is not an OCRA code", index + 1));
+        }
+
+        let response = element.ocra_response(
+            &self.challenge,
+            self.session.as_deref(),
+            current_timestamp(),
+        )?;
+        println!("{response}");
+
+        // Counter based suites need a new counter for each response
+        if element.parsed_ocra_suite()?.counter {
+            database.record_change(index, HistoryAction::CounterChange);
+            if let Some(element) = database.mut_element(index) {
+                element.counter = element.counter.map(|c| c.saturating_add(1));
+            }
+            database.mark_modified();
+        } else {
+            database.record_use(index);
+        }
+        Ok(database)
+    }
+}
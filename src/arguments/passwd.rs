@@ -1,17 +1,69 @@
-use clap::Args;
+use std::path::PathBuf;
+
+use clap::{Args, value_parser};
 use zeroize::Zeroize;
 
-use crate::{otp::otp_element::OTPDatabase, utils};
+use crate::{
+    crypto::{
+        cryptography::{MAX_KDF_MEM_COST, MAX_KDF_TIME_COST},
+        encrypted_database::KdfParams,
+    },
+    otp::otp_element::OTPDatabase,
+    password::PasswordSource,
+    path::database_path,
+};
 
 use super::SubcommandExecutor;
 
 #[derive(Args)]
-pub struct PasswdArgs;
+pub struct PasswdArgs {
+    #[command(flatten)]
+    pub kdf: KdfArgs,
+
+    /// Read the new password from the first line of the given file
+    #[arg(long = "new-password-file")]
+    pub new_password_file: Option<PathBuf>,
+
+    /// Run the given shell command and read the new password from its output
+    #[arg(long = "new-password-command", conflicts_with = "new_password_file")]
+    pub new_password_command: Option<String>,
+}
+
+/// Key derivation parameters which can be customized by the user
+#[derive(Args)]
+pub struct KdfArgs {
+    /// Argon2 memory cost in MiB
+    #[arg(long = "kdf-memory", value_parser = value_parser!(u32).range(1..=i64::from(MAX_KDF_MEM_COST / 1024)))]
+    pub memory: Option<u32>,
+
+    /// Argon2 number of iterations
+    #[arg(long = "kdf-time", value_parser = value_parser!(u32).range(1..=i64::from(MAX_KDF_TIME_COST)))]
+    pub time: Option<u32>,
+
+    /// Argon2 degree of parallelism
+    #[arg(long = "kdf-parallelism", value_parser = value_parser!(u32).range(1..=64))]
+    pub parallelism: Option<u32>,
+}
+
+impl KdfArgs {
+    /// Overrides the given parameters with the ones specified by the user
+    pub fn apply(&self, current: KdfParams) -> KdfParams {
+        KdfParams {
+            mem_cost: self.memory.map_or(current.mem_cost, |m| m * 1024),
+            time_cost: self.time.unwrap_or(current.time_cost),
+            lanes: self.parallelism.unwrap_or(current.lanes),
+            ..current
+        }
+    }
+}
 
 impl SubcommandExecutor for PasswdArgs {
     fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
-        let mut new_password = utils::verified_password("New password: ", 8);
-        database.save_with_pw(&new_password)?;
+        let mut new_password =
+            PasswordSource::from_options(self.new_password_file, self.new_password_command)
+                .read_new("New password: ", 8)?;
+        database.set_kdf(self.kdf.apply(database.kdf()));
+        database.save_with_password(&database_path(), &new_password)?;
         new_password.zeroize();
         Ok(database)
     }
@@ -0,0 +1,54 @@
+use clap::{Args, Subcommand};
+use color_eyre::eyre::eyre;
+
+use crate::{crypto::public_key::parse_recipient, otp::otp_element::OTPDatabase};
+
+use super::SubcommandExecutor;
+
+#[derive(Args)]
+pub struct RecipientArgs {
+    #[command(subcommand)]
+    pub action: RecipientAction,
+}
+
+#[derive(Subcommand)]
+pub enum RecipientAction {
+    /// Allow an age X25519 recipient to unlock the database using its identity
+    Add {
+        /// age recipient, like age1...
+        recipient: String,
+    },
+    /// Revoke the access of an age recipient. Change the password afterwards to also
+    /// rotate the database key
+    Remove {
+        /// age recipient, like age1...
+        recipient: String,
+    },
+    /// List the age recipients which can unlock the database
+    List,
+}
+
+impl SubcommandExecutor for RecipientArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        match self.action {
+            RecipientAction::Add { recipient } => {
+                let recipient = parse_recipient(&recipient)?.to_string();
+                if !database.add_recipient(recipient.clone()) {
+                    return Err(eyre!("{recipient} is already a recipient"));
+                }
+            }
+            RecipientAction::Remove { recipient } => {
+                if !database.remove_recipient(recipient.trim()) {
+                    return Err(eyre!("{recipient} is not a recipient"));
+                }
+            }
+            RecipientAction::List => {
+                if database.recipients().is_empty() {
+                    println!("No recipients, the database can only be unlocked with the password");
+                }
+                database.recipients().iter().for_each(|r| println!("{r}"));
+            }
+        }
+        Ok(database)
+    }
+}
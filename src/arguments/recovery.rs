@@ -0,0 +1,84 @@
+use std::io::{self, BufRead};
+
+use clap::{Args, Subcommand};
+use color_eyre::eyre::eyre;
+
+use crate::otp::otp_element::OTPDatabase;
+
+use super::{CodeTarget, SubcommandExecutor};
+
+#[derive(Args)]
+pub struct RecoveryArgs {
+    #[command(subcommand)]
+    pub action: RecoveryAction,
+}
+
+#[derive(Subcommand)]
+pub enum RecoveryAction {
+    /// Store new recovery codes, read one per line from the standard input if none is given
+    Add {
+        #[command(flatten)]
+        target: CodeTarget,
+        /// Recovery codes
+        codes: Vec<String>,
+    },
+    /// Show the recovery codes of a code
+    List {
+        #[command(flatten)]
+        target: CodeTarget,
+    },
+    /// Mark a recovery code as used, or print and mark the first unused one if none is given
+    Use {
+        #[command(flatten)]
+        target: CodeTarget,
+        /// Recovery code
+        code: Option<String>,
+    },
+}
+
+impl SubcommandExecutor for RecoveryArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        match self.action {
+            RecoveryAction::Add { target, mut codes } => {
+                let index = target.resolve(&database)?;
+                if codes.is_empty() {
+                    codes = io::stdin().lock().lines().collect::<Result<_, _>>()?;
+                }
+                let element = database.mut_element(index).unwrap();
+                let added = element.add_recovery_codes(codes.iter().map(String::as_str));
+                println!("Added {added} recovery codes");
+                if added > 0 {
+                    database.mark_modified();
+                }
+            }
+            RecoveryAction::List { target } => {
+                let element = database.get_element(target.resolve(&database)?).unwrap();
+                element.recovery_codes.iter().for_each(|r| {
+                    let marker = if r.used { "x" } else { " " };
+                    println!("[{marker}] {}", r.code);
+                });
+                println!(
+                    "{} of {} recovery codes unused",
+                    element.unused_recovery_codes(),
+                    element.recovery_codes.len()
+                );
+            }
+            RecoveryAction::Use { target, code } => {
+                let index = target.resolve(&database)?;
+                let element = database.mut_element(index).unwrap();
+                let used = element
+                    .use_recovery_code(code.as_deref())
+                    .map_err(|e| eyre!(e))?;
+                println!("{used}");
+                let unused = element.unused_recovery_codes();
+                if unused == 0 {
+                    eprintln!("Warning: no unused recovery codes left, generate new ones");
+                } else {
+                    eprintln!("{unused} recovery codes left");
+                }
+                database.mark_modified();
+            }
+        }
+        Ok(database)
+    }
+}
@@ -0,0 +1,78 @@
+use std::io::{self, Write};
+
+use clap::{Args, Subcommand};
+use color_eyre::eyre::eyre;
+
+use crate::{otp::otp_element::OTPDatabase, utils::format_date};
+
+use super::{CodeTarget, SubcommandExecutor, delete::read_confirmation_line};
+
+#[derive(Args)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    pub action: TrashAction,
+}
+
+#[derive(Subcommand)]
+pub enum TrashAction {
+    /// List the codes in the trash
+    List,
+    /// Move a code back from the trash
+    Restore {
+        #[command(flatten)]
+        target: CodeTarget,
+    },
+    /// Permanently delete all the codes in the trash
+    Purge,
+    /// Permanently delete the codes which have been in the trash for the given days, 0 disables it
+    AutoPurge {
+        /// Days
+        days: u64,
+    },
+}
+
+impl SubcommandExecutor for TrashArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        match self.action {
+            TrashAction::List => {
+                let archived = database.archived_indexes();
+                if archived.is_empty() {
+                    println!("The trash is empty");
+                }
+                archived.into_iter().for_each(|i| {
+                    let element = &database.elements_ref()[i];
+                    println!(
+                        "{0: <6} {1}  {2} ({3})",
+                        i + 1,
+                        format_date(element.archived_at.unwrap_or_default()),
+                        element.issuer,
+                        element.label
+                    );
+                });
+                if let Some(days) = database.trash_retention_days() {
+                    println!("Codes are permanently deleted after {days} days in the trash");
+                }
+            }
+            TrashAction::Restore { target } => {
+                let index = target.resolve(&database)?;
+                database.restore_element(index).map_err(|e| eyre!(e))?;
+            }
+            TrashAction::Purge => {
+                let count = database.archived_indexes().len();
+                if count == 0 {
+                    return Err(eyre!("The trash is empty"));
+                }
+                print!("Are you sure you want to permanently delete {count} codes [Y,N]: ");
+                io::stdout().flush()?;
+                if !read_confirmation_line()?.trim().eq_ignore_ascii_case("y") {
+                    return Err(eyre!("Operation interrupt by the user"));
+                }
+                database.purge_trash();
+            }
+            TrashAction::AutoPurge { days } => {
+                database.set_trash_retention_days((days > 0).then_some(days));
+            }
+        }
+        Ok(database)
+    }
+}
@@ -0,0 +1,23 @@
+use clap::Args;
+use color_eyre::eyre::eyre;
+
+use crate::otp::otp_element::OTPDatabase;
+
+use super::SubcommandExecutor;
+
+#[derive(Args)]
+pub struct UndoArgs {
+    /// Number of changes to revert, starting from the most recent one
+    #[arg(default_value_t = 1)]
+    pub count: usize,
+}
+
+impl SubcommandExecutor for UndoArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        for _ in 0..self.count {
+            let entry = database.undo().map_err(|e| eyre!(e))?;
+            println!("Undone: {entry}");
+        }
+        Ok(database)
+    }
+}
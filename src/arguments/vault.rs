@@ -0,0 +1,118 @@
+use std::{
+    fs,
+    io::{self, Write},
+};
+
+use clap::{Args, Subcommand};
+use color_eyre::eyre::eyre;
+use zeroize::Zeroize;
+
+use crate::{
+    create_database,
+    crypto::encrypted_database::KdfParams,
+    path::{
+        DEFAULT_VAULT_NAME, default_vault, existing_vault_path, list_vaults, set_database_path,
+        set_default_vault, vault_path,
+    },
+};
+
+use super::{CotpArgs, delete::read_confirmation_line};
+
+#[derive(Args)]
+pub struct VaultArgs {
+    #[command(subcommand)]
+    pub action: VaultAction,
+}
+
+#[derive(Subcommand)]
+pub enum VaultAction {
+    /// List the available vaults
+    List,
+    /// Create a new vault protected by its own password
+    Create {
+        /// Vault name
+        name: String,
+    },
+    /// Permanently delete a vault and all its codes
+    Remove {
+        /// Vault name
+        name: String,
+    },
+    /// Rename a vault
+    Rename {
+        /// Current vault name
+        name: String,
+        /// New vault name
+        new_name: String,
+    },
+    /// Set the vault to open when no one is specified
+    Default {
+        /// Vault name
+        name: String,
+    },
+}
+
+impl VaultArgs {
+    pub fn run(&self, cotp_args: &CotpArgs) -> color_eyre::Result<()> {
+        match &self.action {
+            VaultAction::List => {
+                let default = default_vault().unwrap_or(DEFAULT_VAULT_NAME.to_string());
+                list_vaults().into_iter().for_each(|vault| {
+                    let marker = if vault == default { "*" } else { " " };
+                    println!("{marker} {vault}");
+                });
+            }
+            VaultAction::Create { name } => {
+                let path = vault_path(name)?;
+                if path.exists() {
+                    return Err(eyre!("Vault {name} already exists"));
+                }
+                fs::create_dir_all(path.parent().unwrap())?;
+                set_database_path(path);
+                let (_, mut key, _) =
+                    create_database(&cotp_args.password_source()?, KdfParams::default())?;
+                key.zeroize();
+                println!("Vault {name} created");
+            }
+            VaultAction::Remove { name } => {
+                if name == DEFAULT_VAULT_NAME {
+                    return Err(eyre!("The default vault cannot be removed"));
+                }
+                let path = existing_vault_path(name)?;
+                print!(
+                    "Are you sure you want to delete the vault {name} and all its codes? [Y,N]: "
+                );
+                io::stdout().flush()?;
+                if !read_confirmation_line()?.trim().eq_ignore_ascii_case("y") {
+                    return Err(eyre!("Operation interrupt by the user"));
+                }
+                fs::remove_file(path)?;
+                if default_vault().as_ref() == Some(name) {
+                    set_default_vault(DEFAULT_VAULT_NAME)?;
+                }
+                println!("Vault {name} removed");
+            }
+            VaultAction::Rename { name, new_name } => {
+                if name == DEFAULT_VAULT_NAME || new_name == DEFAULT_VAULT_NAME {
+                    return Err(eyre!("The default vault cannot be renamed"));
+                }
+                let path = existing_vault_path(name)?;
+                let new_path = vault_path(new_name)?;
+                if new_path.exists() {
+                    return Err(eyre!("Vault {new_name} already exists"));
+                }
+                fs::rename(path, new_path)?;
+                if default_vault().as_ref() == Some(name) {
+                    set_default_vault(new_name)?;
+                }
+                println!("Vault {name} renamed to {new_name}");
+            }
+            VaultAction::Default { name } => {
+                existing_vault_path(name)?;
+                set_default_vault(name)?;
+                println!("Vault {name} will be opened by default");
+            }
+        }
+        Ok(())
+    }
+}
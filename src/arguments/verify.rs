@@ -0,0 +1,60 @@
+use clap::{Args, value_parser};
+use color_eyre::eyre::eyre;
+
+use crate::{
+    otp::{history::HistoryAction, otp_element::OTPDatabase, otp_type::OTPType},
+    utils::current_timestamp,
+};
+
+use super::{CodeTarget, SubcommandExecutor};
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[command(flatten)]
+    pub target: CodeTarget,
+
+    /// Code to verify
+    pub code: String,
+
+    /// Number of time steps accepted before and after the current one, or HOTP counters after the stored one
+    #[arg(short, long, default_value_t = 1, value_parser = value_parser!(u64).range(0..=100))]
+    pub window: u64,
+
+    /// Store the counter following the matching one, for HOTP codes
+    #[arg(long, default_value_t = false)]
+    pub resync: bool,
+}
+
+impl SubcommandExecutor for VerifyArgs {
+    fn run_command(self, mut database: OTPDatabase) -> color_eyre::Result<OTPDatabase> {
+        let index = self.target.resolve(&database)?;
+        let element = &database.elements_ref()[index];
+        if self.resync && element.type_ != OTPType::Hotp {
+            return Err(eyre!("Only HOTP counters can be resynchronized"));
+        }
+
+        let step = element
+            .verify(&self.code, self.window, current_timestamp())?
+            .ok_or(eyre!("The code does not match"))?;
+
+        if element.type_ == OTPType::Totp {
+            match step {
+                0 => println!("The code matches the current time step"),
+                _ => println!("The code matches the time step {step:+}"),
+            }
+            return Ok(database);
+        }
+
+        let counter = element.counter.unwrap_or_default() + step as u64;
+        println!("The code matches the counter {counter} ({step} ahead)");
+        if self.resync {
+            database.record_change(index, HistoryAction::CounterChange);
+            if let Some(element) = database.mut_element(index) {
+                element.counter = Some(counter + 1);
+            }
+            database.mark_modified();
+            println!("Counter set to {}", counter + 1);
+        }
+        Ok(database)
+    }
+}
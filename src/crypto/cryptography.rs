@@ -1,28 +1,77 @@
+use std::path::Path;
+use std::time::{Duration, Instant};
+
 use argon2::{Config, ThreadMode, Variant, Version};
 use chacha20poly1305::aead::Aead;
 use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
 use color_eyre::eyre::{ErrReport, eyre};
 use data_encoding::BASE64;
 
-use super::encrypted_database::EncryptedDatabase;
+use super::encrypted_database::{
+    CURRENT_ENCRYPTED_DATABASE_VERSION, EncryptedDatabase, KdfAlgorithm, KdfParams,
+};
+use super::public_key::{decrypt_with_identity_file, encrypt_to_recipients};
 
 const ARGON2ID_SALT_LENGTH: usize = 16;
 const XCHACHA20_POLY1305_NONCE_LENGTH: usize = 24;
 const XCHACHA20_POLY1305_KEY_LENGTH: usize = 32;
-const KEY_DERIVATION_CONFIG: Config = Config {
-    variant: Variant::Argon2id,
-    version: Version::Version13,
-    mem_cost: 32768,
-    time_cost: 4,
-    lanes: 4,
-    secret: &[],
-    ad: &[],
-    hash_length: XCHACHA20_POLY1305_KEY_LENGTH as u32,
-    thread_mode: ThreadMode::Sequential,
-};
+const BENCHMARK_MAX_MEM_COST: u32 = 1024 * 1024;
+/// Highest Argon2 number of iterations accepted, so a typo cannot make the database impossible to unlock
+pub const MAX_KDF_TIME_COST: u32 = 100;
+/// Highest Argon2 memory cost accepted in KiB, since the header is read before the password is checked
+pub const MAX_KDF_MEM_COST: u32 = 4 * 1024 * 1024;
+
+fn key_derivation_config(params: &KdfParams) -> Config<'static> {
+    let variant = match params.algorithm {
+        KdfAlgorithm::Argon2id => Variant::Argon2id,
+    };
+    Config {
+        variant,
+        version: Version::Version13,
+        mem_cost: params.mem_cost,
+        time_cost: params.time_cost,
+        lanes: params.lanes,
+        secret: &[],
+        ad: &[],
+        hash_length: XCHACHA20_POLY1305_KEY_LENGTH as u32,
+        thread_mode: ThreadMode::Sequential,
+    }
+}
+
+pub fn argon_derive_key(
+    password_bytes: &[u8],
+    salt: &[u8],
+    params: &KdfParams,
+) -> color_eyre::Result<Vec<u8>> {
+    argon2::hash_raw(password_bytes, salt, &key_derivation_config(params))
+        .map_err(|e| eyre!("Error during key derivation: {e}"))
+}
 
-pub fn argon_derive_key(password_bytes: &[u8], salt: &[u8]) -> color_eyre::Result<Vec<u8>> {
-    argon2::hash_raw(password_bytes, salt, &KEY_DERIVATION_CONFIG).map_err(ErrReport::from)
+/// Measures how long a key derivation with the given parameters takes on this machine
+pub fn benchmark_kdf(params: &KdfParams) -> color_eyre::Result<Duration> {
+    let salt = gen_salt()?;
+    let start = Instant::now();
+    argon_derive_key(b"cotp-kdf-benchmark", &salt, params)?;
+    Ok(start.elapsed())
+}
+
+/// Suggests key derivation parameters taking roughly `target` to derive a key on this machine.
+/// The memory cost is doubled first, since it is the most effective defense against
+/// hardware attacks, then the number of iterations fills the remaining time.
+pub fn suggest_kdf_params(target: Duration, lanes: u32) -> color_eyre::Result<KdfParams> {
+    let mut params = KdfParams {
+        time_cost: 1,
+        lanes,
+        ..Default::default()
+    };
+    let mut elapsed = benchmark_kdf(&params)?;
+    while elapsed * 4 < target && params.mem_cost * 2 <= BENCHMARK_MAX_MEM_COST {
+        params.mem_cost *= 2;
+        elapsed = benchmark_kdf(&params)?;
+    }
+    let iterations = target.as_secs_f64() / elapsed.as_secs_f64().max(f64::EPSILON);
+    params.time_cost = (iterations.round() as u32).clamp(1, MAX_KDF_TIME_COST);
+    Ok(params)
 }
 
 pub fn gen_salt() -> color_eyre::Result<[u8; ARGON2ID_SALT_LENGTH]> {
@@ -35,6 +84,7 @@ pub fn encrypt_string_with_key(
     plain_text: &str,
     key: &Vec<u8>,
     salt: &[u8],
+    kdf: KdfParams,
 ) -> color_eyre::Result<EncryptedDatabase> {
     let aead = XChaCha20Poly1305::new_from_slice(key.as_slice())
         .map_err(|e| eyre!("Invalid encryption key length: {e}"))?;
@@ -48,20 +98,88 @@ pub fn encrypt_string_with_key(
         .encrypt(&nonce, plain_text.as_bytes())
         .map_err(|e| eyre!("Error during encryption: {e}"))?;
     Ok(EncryptedDatabase::new(
-        1,
+        CURRENT_ENCRYPTED_DATABASE_VERSION,
+        kdf,
         BASE64.encode(&nonce_bytes),
         BASE64.encode(salt),
         BASE64.encode(&cipher_text),
     ))
 }
 
+/// Decrypted database contents along with the key material needed to encrypt them again
+pub struct DecryptedDatabase {
+    pub plain_text: String,
+    pub key: Vec<u8>,
+    pub salt: Vec<u8>,
+    pub kdf: KdfParams,
+    /// Recipients listed in the unauthenticated header, only used to detect tampering
+    pub header_recipients: Vec<String>,
+}
+
+/// Encrypts the database key to the given age recipients and stores it in the database header,
+/// so the database can also be unlocked using one of their identities
+pub fn add_recipients_key(
+    encrypted_database: &mut EncryptedDatabase,
+    key: &[u8],
+    recipients: &[String],
+) -> color_eyre::Result<()> {
+    if !recipients.is_empty() {
+        let recipients_key = encrypt_to_recipients(key, recipients)?;
+        encrypted_database.set_recipients_key(BASE64.encode(&recipients_key));
+    }
+    Ok(())
+}
+
 pub fn decrypt_string(
     encrypted_text: &str,
     password: &str,
-) -> color_eyre::Result<(String, Vec<u8>, Vec<u8>)> {
+) -> color_eyre::Result<DecryptedDatabase> {
+    let encrypted_database = parse_encrypted_database(encrypted_text)?;
+    let salt = BASE64.decode(encrypted_database.salt().as_bytes()).unwrap();
+    let kdf = encrypted_database.kdf();
+    let key: Vec<u8> = argon_derive_key(password.as_bytes(), salt.as_slice(), &kdf)?;
+    decrypt_with_key(&encrypted_database, key).map_err(|_| eyre!("Wrong password"))
+}
+
+/// Decrypts the database using the key stored for its age recipients
+pub fn decrypt_string_with_identity(
+    encrypted_text: &str,
+    identity_file: &Path,
+) -> color_eyre::Result<DecryptedDatabase> {
+    let encrypted_database = parse_encrypted_database(encrypted_text)?;
+    let recipients_key = encrypted_database
+        .recipients_key()
+        .ok_or(eyre!("The database has no age recipients"))?;
+    let recipients_key = BASE64
+        .decode(recipients_key.as_bytes())
+        .map_err(|e| eyre!("Cannot decode Base64 recipients key: {e}"))?;
+    let key = decrypt_with_identity_file(&recipients_key, identity_file)?;
+    decrypt_with_key(&encrypted_database, key)
+}
+
+fn parse_encrypted_database(encrypted_text: &str) -> color_eyre::Result<EncryptedDatabase> {
     //encrypted text is an encrypted database json serialized object
     let encrypted_database: EncryptedDatabase = serde_json::from_str(encrypted_text)
         .map_err(|e| eyre!("Error during encrypted database deserialization: {e}"))?;
+    if encrypted_database.version() > CURRENT_ENCRYPTED_DATABASE_VERSION {
+        return Err(eyre!(
+            "The database has been created by a newer version of cotp, please update"
+        ));
+    }
+    // The header is not authenticated, so it must not make the key derivation exhaust the memory
+    let kdf = encrypted_database.kdf();
+    if kdf.mem_cost > MAX_KDF_MEM_COST || kdf.time_cost > MAX_KDF_TIME_COST {
+        return Err(eyre!(
+            "The key derivation parameters of the database are out of range, the file may be corrupted"
+        ));
+    }
+    Ok(encrypted_database)
+}
+
+fn decrypt_with_key(
+    encrypted_database: &EncryptedDatabase,
+    key: Vec<u8>,
+) -> color_eyre::Result<DecryptedDatabase> {
     let nonce = BASE64
         .decode(encrypted_database.nonce().as_bytes())
         .expect("Cannot decode Base64 nonce");
@@ -70,32 +188,135 @@ pub fn decrypt_string(
         .expect("Cannot decode Base64 cipher");
     let salt = BASE64.decode(encrypted_database.salt().as_bytes()).unwrap();
 
-    let key: Vec<u8> = argon_derive_key(password.as_bytes(), salt.as_slice())?;
-
     let aead = XChaCha20Poly1305::new_from_slice(&key)
         .map_err(|e| eyre!("Invalid encryption key length: {e}"))?;
     let nonce = XNonce::try_from(nonce.as_slice())
         .map_err(|_| eyre!("Invalid nonce length in encrypted database"))?;
     let decrypted = aead
         .decrypt(&nonce, cipher_text.as_slice())
-        .map_err(|_| eyre!("Wrong password"))?;
-    let from_utf8 = String::from_utf8(decrypted).map_err(ErrReport::from)?;
-    Ok((from_utf8, key, salt))
+        .map_err(|_| eyre!("Cannot decrypt the database"))?;
+    let plain_text = String::from_utf8(decrypted).map_err(ErrReport::from)?;
+    Ok(DecryptedDatabase {
+        plain_text,
+        key,
+        salt,
+        kdf: encrypted_database.kdf(),
+        header_recipients: encrypted_database.recipients().to_vec(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::crypto::cryptography::{argon_derive_key, gen_salt};
+    use age::{secrecy::ExposeSecret, x25519};
+    use assert_fs::{NamedTempFile, prelude::FileWriteStr};
 
-    use super::{decrypt_string, encrypt_string_with_key};
+    use crate::crypto::cryptography::{MAX_KDF_MEM_COST, argon_derive_key, gen_salt};
+    use crate::crypto::encrypted_database::KdfParams;
+
+    use super::{
+        add_recipients_key, decrypt_string, decrypt_string_with_identity, encrypt_string_with_key,
+    };
 
     #[test]
     fn test_encryption() {
         let salt = gen_salt().unwrap();
-        let key = argon_derive_key(b"pa$$w0rd", salt.as_ref()).unwrap();
-        let encrypted = encrypt_string_with_key("Secret data@#[]ò", &key, salt.as_ref()).unwrap();
-        let (decrypted, _key, _salt) =
+        let kdf = KdfParams::default();
+        let key = argon_derive_key(b"pa$$w0rd", salt.as_ref(), &kdf).unwrap();
+        let encrypted =
+            encrypt_string_with_key("Secret data@#[]ò", &key, salt.as_ref(), kdf).unwrap();
+        let decrypted =
+            decrypt_string(&serde_json::to_string(&encrypted).unwrap(), "pa$$w0rd").unwrap();
+        assert_eq!(String::from("Secret data@#[]ò"), decrypted.plain_text);
+    }
+
+    #[test]
+    fn test_encryption_with_custom_kdf_params() {
+        // Arrange
+        let salt = gen_salt().unwrap();
+        let kdf = KdfParams {
+            mem_cost: 8192,
+            time_cost: 1,
+            lanes: 1,
+            ..Default::default()
+        };
+        let key = argon_derive_key(b"pa$$w0rd", salt.as_ref(), &kdf).unwrap();
+
+        // Act
+        let encrypted = encrypt_string_with_key("Secret data", &key, salt.as_ref(), kdf).unwrap();
+        let decrypted =
             decrypt_string(&serde_json::to_string(&encrypted).unwrap(), "pa$$w0rd").unwrap();
-        assert_eq!(String::from("Secret data@#[]ò"), decrypted);
+
+        // Assert
+        assert_eq!(String::from("Secret data"), decrypted.plain_text);
+        assert_eq!(kdf, decrypted.kdf);
+    }
+
+    #[test]
+    fn test_version_1_header_uses_legacy_kdf_params() {
+        // Arrange
+        let salt = gen_salt().unwrap();
+        let key = argon_derive_key(b"pa$$w0rd", salt.as_ref(), &KdfParams::default()).unwrap();
+        let encrypted =
+            encrypt_string_with_key("Secret data", &key, salt.as_ref(), KdfParams::default())
+                .unwrap();
+        let mut legacy_json = serde_json::to_value(&encrypted).unwrap();
+        let legacy_header = legacy_json.as_object_mut().unwrap();
+        legacy_header.remove("kdf");
+        legacy_header.insert("version".to_string(), 1.into());
+
+        // Act
+        let decrypted = decrypt_string(&legacy_json.to_string(), "pa$$w0rd").unwrap();
+
+        // Assert
+        assert_eq!(String::from("Secret data"), decrypted.plain_text);
+        assert_eq!(KdfParams::default(), decrypted.kdf);
+    }
+
+    #[test]
+    fn test_out_of_range_kdf_params_are_rejected() {
+        // Arrange
+        let salt = gen_salt().unwrap();
+        let key = argon_derive_key(b"pa$$w0rd", salt.as_ref(), &KdfParams::default()).unwrap();
+        let encrypted =
+            encrypt_string_with_key("Secret data", &key, salt.as_ref(), KdfParams::default())
+                .unwrap();
+        let mut header = serde_json::to_value(&encrypted).unwrap();
+        header["kdf"]["mem_cost"] = (MAX_KDF_MEM_COST + 1).into();
+
+        // Act
+        let decrypted = decrypt_string(&header.to_string(), "pa$$w0rd");
+
+        // Assert
+        assert!(decrypted.is_err());
+    }
+
+    #[test]
+    fn test_decryption_with_recipient_identity() {
+        // Arrange
+        let identity = x25519::Identity::generate();
+        let identity_file = NamedTempFile::new("identity.txt").unwrap();
+        identity_file
+            .write_str(identity.to_string().expose_secret())
+            .unwrap();
+        let recipients = vec![identity.to_public().to_string()];
+
+        let salt = gen_salt().unwrap();
+        let key = argon_derive_key(b"pa$$w0rd", salt.as_ref(), &KdfParams::default()).unwrap();
+        let mut encrypted =
+            encrypt_string_with_key("Secret data", &key, salt.as_ref(), KdfParams::default())
+                .unwrap();
+        add_recipients_key(&mut encrypted, &key, &recipients).unwrap();
+
+        // Act
+        let decrypted = decrypt_string_with_identity(
+            &serde_json::to_string(&encrypted).unwrap(),
+            identity_file.path(),
+        )
+        .unwrap();
+
+        // Assert
+        assert_eq!(String::from("Secret data"), decrypted.plain_text);
+        assert_eq!(key, decrypted.key);
+        assert!(decrypted.header_recipients.is_empty());
     }
 }
@@ -1,23 +1,88 @@
 use serde::{Deserialize, Serialize};
 
+/// Current version of the encrypted database header
+pub const CURRENT_ENCRYPTED_DATABASE_VERSION: u16 = 2;
+
+/// Key derivation algorithms supported to derive the database key from the password
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum KdfAlgorithm {
+    /// Argon2id, the only algorithm used so far
+    #[default]
+    Argon2id,
+}
+
+/// Key derivation parameters stored in the encrypted database header.
+/// Version 1 databases do not carry them, so the defaults match the values they were created with.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Hash)]
+pub struct KdfParams {
+    /// Algorithm used to derive the key
+    pub algorithm: KdfAlgorithm,
+    /// Memory cost in KiB
+    pub mem_cost: u32,
+    /// Number of iterations
+    pub time_cost: u32,
+    /// Degree of parallelism
+    pub lanes: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            algorithm: KdfAlgorithm::Argon2id,
+            mem_cost: 32768,
+            time_cost: 4,
+            lanes: 4,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct EncryptedDatabase {
     version: u16,
+    #[serde(default)]
+    kdf: KdfParams,
     nonce: String,
     salt: String,
     cipher: String,
+    /// age X25519 recipients listed by older versions, which are not authenticated.
+    /// The recipients are now stored in the encrypted database.
+    #[serde(default, skip_serializing)]
+    recipients: Vec<String>,
+    /// Database key encrypted to the recipients, then encoded in Base64
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recipients_key: Option<String>,
 }
 
 impl EncryptedDatabase {
-    pub fn new(version: u16, nonce: String, salt: String, cipher: String) -> EncryptedDatabase {
+    pub fn new(
+        version: u16,
+        kdf: KdfParams,
+        nonce: String,
+        salt: String,
+        cipher: String,
+    ) -> EncryptedDatabase {
         EncryptedDatabase {
             version,
+            kdf,
             nonce,
             salt,
             cipher,
+            recipients: vec![],
+            recipients_key: None,
         }
     }
 
+    pub fn set_recipients_key(&mut self, recipients_key: String) {
+        self.recipients_key = Some(recipients_key);
+    }
+
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+    pub fn kdf(&self) -> KdfParams {
+        self.kdf
+    }
     pub fn nonce(&self) -> &str {
         &self.nonce
     }
@@ -27,4 +92,10 @@ impl EncryptedDatabase {
     pub fn cipher(&self) -> &str {
         &self.cipher
     }
+    pub fn recipients(&self) -> &[String] {
+        &self.recipients
+    }
+    pub fn recipients_key(&self) -> Option<&str> {
+        self.recipients_key.as_deref()
+    }
 }
@@ -1,2 +1,3 @@
 pub mod cryptography;
 pub mod encrypted_database;
+pub mod public_key;
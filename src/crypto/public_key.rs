@@ -0,0 +1,98 @@
+use std::io::{Read, Write};
+use std::path::Path;
+
+use age::{Decryptor, Encryptor, IdentityFile, x25519};
+use color_eyre::eyre::eyre;
+
+/// Parses an age X25519 recipient, like `age1...`
+pub fn parse_recipient(recipient: &str) -> color_eyre::Result<x25519::Recipient> {
+    recipient
+        .trim()
+        .parse::<x25519::Recipient>()
+        .map_err(|e| eyre!("Invalid age recipient {recipient}: {e}"))
+}
+
+/// Encrypts the given data to all the given age X25519 recipients
+pub fn encrypt_to_recipients(plain: &[u8], recipients: &[String]) -> color_eyre::Result<Vec<u8>> {
+    let parsed = recipients
+        .iter()
+        .map(|r| parse_recipient(r))
+        .collect::<color_eyre::Result<Vec<x25519::Recipient>>>()?;
+
+    let encryptor = Encryptor::with_recipients(parsed.iter().map(|r| r as &dyn age::Recipient))
+        .map_err(|e| eyre!("Error during age encryption: {e}"))?;
+
+    let mut encrypted = Vec::with_capacity(plain.len());
+    let mut writer = encryptor.wrap_output(&mut encrypted)?;
+    writer.write_all(plain)?;
+    writer.finish()?;
+    Ok(encrypted)
+}
+
+/// Decrypts age encrypted data using any of the identities contained in the given identity file
+pub fn decrypt_with_identity_file(
+    encrypted: &[u8],
+    identity_file: &Path,
+) -> color_eyre::Result<Vec<u8>> {
+    let identities = IdentityFile::from_file(identity_file.to_string_lossy().into_owned())
+        .map_err(|e| eyre!("Cannot read identity file: {e}"))?
+        .into_identities()
+        .map_err(|e| eyre!("Invalid identity file: {e}"))?;
+
+    let decryptor =
+        Decryptor::new(encrypted).map_err(|e| eyre!("Invalid age encrypted data: {e}"))?;
+    let mut reader = decryptor
+        .decrypt(identities.iter().map(|i| i.as_ref() as &dyn age::Identity))
+        .map_err(|e| eyre!("Cannot decrypt using the given identity: {e}"))?;
+
+    let mut decrypted = vec![];
+    reader.read_to_end(&mut decrypted)?;
+    Ok(decrypted)
+}
+
+#[cfg(test)]
+mod tests {
+    use age::{secrecy::ExposeSecret, x25519};
+    use assert_fs::{NamedTempFile, prelude::FileWriteStr};
+
+    use super::{decrypt_with_identity_file, encrypt_to_recipients};
+
+    #[test]
+    fn test_encrypt_and_decrypt_with_identity_file() {
+        // Arrange
+        let first = x25519::Identity::generate();
+        let second = x25519::Identity::generate();
+        let recipients = vec![
+            first.to_public().to_string(),
+            second.to_public().to_string(),
+        ];
+        let identity_file = NamedTempFile::new("identity.txt").unwrap();
+        identity_file
+            .write_str(second.to_string().expose_secret())
+            .unwrap();
+
+        // Act
+        let encrypted = encrypt_to_recipients(b"Secret data", &recipients).unwrap();
+        let decrypted = decrypt_with_identity_file(&encrypted, identity_file.path()).unwrap();
+
+        // Assert
+        assert_eq!(b"Secret data".to_vec(), decrypted);
+    }
+
+    #[test]
+    fn test_decrypt_with_wrong_identity_fails() {
+        // Arrange
+        let recipient = x25519::Identity::generate().to_public().to_string();
+        let identity_file = NamedTempFile::new("identity.txt").unwrap();
+        identity_file
+            .write_str(x25519::Identity::generate().to_string().expose_secret())
+            .unwrap();
+
+        // Act
+        let encrypted = encrypt_to_recipients(b"Secret data", &[recipient]).unwrap();
+        let result = decrypt_with_identity_file(&encrypted, identity_file.path());
+
+        // Assert
+        assert!(result.is_err());
+    }
+}
@@ -3,11 +3,19 @@ use std::{fs::File, io::Write, path::PathBuf};
 use serde::Serialize;
 use zeroize::Zeroize;
 
+use crate::crypto::public_key::encrypt_to_recipients;
+
 pub mod andotp;
 pub mod freeotp_plus;
 pub mod otp_uri;
 
-pub fn do_export<T>(to_be_saved: &T, exported_path: PathBuf) -> Result<PathBuf, String>
+/// Serializes the given backup into the given path.
+/// If any age recipient is given, the backup is encrypted to them.
+pub fn do_export<T>(
+    to_be_saved: &T,
+    exported_path: PathBuf,
+    recipients: &[String],
+) -> Result<PathBuf, String>
 where
     T: ?Sized + Serialize,
 {
@@ -16,11 +24,16 @@ where
             if contents == "[]" {
                 return Err("No contents to export, skipping...".to_owned());
             }
+            let mut contents_bytes = if recipients.is_empty() {
+                contents.as_bytes().to_vec()
+            } else {
+                encrypt_to_recipients(contents.as_bytes(), recipients).map_err(|e| e.to_string())?
+            };
+            contents.zeroize();
             let mut file = File::create(&exported_path).expect("Cannot create file");
-            let contents_bytes = contents.as_bytes();
-            file.write_all(contents_bytes)
+            file.write_all(&contents_bytes)
                 .expect("Failed to write contents");
-            contents.zeroize();
+            contents_bytes.zeroize();
             Ok(exported_path)
         }
         Err(e) => Err(format!("{e:?}")),
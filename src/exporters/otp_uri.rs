@@ -1,8 +1,10 @@
 use crate::otp::otp_element::OTPDatabase;
 use serde::{Deserialize, Serialize};
 
+/// List of `otpauth://` URIs, one for each exported code
 #[derive(Serialize, Deserialize)]
 pub struct OtpUriList {
+    /// The exported URIs
     pub items: Vec<String>,
 }
 
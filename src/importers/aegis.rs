@@ -1,7 +1,16 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType};
+use crate::otp::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPElement},
+    otp_type::OTPType,
+};
 
+/// Plain Aegis backup
 #[derive(Serialize, Deserialize)]
 pub struct AegisJson {
     //version: u64,
@@ -20,6 +29,14 @@ struct AegisHeader {
 pub(crate) struct AegisDb {
     //version: u64,
     entries: Vec<AegisElement>,
+    #[serde(default)]
+    groups: Vec<AegisGroup>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AegisGroup {
+    uuid: String,
+    name: String,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -30,6 +47,32 @@ struct AegisElement {
     issuer: String,
     //icon: Option<String>,
     info: AegisInfo,
+    #[serde(default)]
+    note: Option<String>,
+    /// Group name, used before Aegis introduced multiple groups per entry
+    #[serde(default)]
+    group: Option<String>,
+    /// Uuids of the groups the entry belongs to
+    #[serde(default)]
+    groups: Vec<String>,
+}
+
+impl AegisElement {
+    fn into_otp_element(self, groups: &HashMap<String, String>) -> OTPElement {
+        let tags: Vec<String> = self
+            .group
+            .iter()
+            .cloned()
+            .chain(
+                self.groups
+                    .iter()
+                    .filter_map(|uuid| groups.get(uuid).cloned()),
+            )
+            .collect();
+        let mut element: OTPElement = self.into();
+        element.add_tags(&tags);
+        element
+    }
 }
 
 impl From<AegisElement> for OTPElement {
@@ -44,6 +87,22 @@ impl From<AegisElement> for OTPElement {
             period: value.info.period.unwrap_or(30),
             counter: value.info.counter,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: value.note.filter(|n| !n.trim().is_empty()),
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         }
     }
 }
@@ -52,7 +111,16 @@ impl TryFrom<AegisDb> for Vec<OTPElement> {
     type Error = String;
 
     fn try_from(aegis_db: AegisDb) -> Result<Self, Self::Error> {
-        Ok(aegis_db.entries.into_iter().map(Into::into).collect())
+        let groups: HashMap<String, String> = aegis_db
+            .groups
+            .into_iter()
+            .map(|g| (g.uuid, g.name))
+            .collect();
+        Ok(aegis_db
+            .entries
+            .into_iter()
+            .map(|e| e.into_otp_element(&groups))
+            .collect())
     }
 }
 
@@ -72,3 +140,79 @@ struct AegisInfo {
     period: Option<u64>,
     counter: Option<u64>,
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::importers::importer::import_from_str;
+    use crate::otp::otp_element::OTPElement;
+
+    use super::AegisJson;
+
+    #[test]
+    fn test_groups_are_mapped_to_tags() {
+        // Arrange
+        let json = r#"{
+            "db": {
+                "entries": [
+                    {
+                        "type": "totp",
+                        "name": "label",
+                        "issuer": "issuer",
+                        "groups": ["6e5cbf34-4aa8-4b18-a76b-1d2e9e3a2f40"],
+                        "info": { "secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30 }
+                    },
+                    {
+                        "type": "totp",
+                        "name": "legacy",
+                        "issuer": "issuer",
+                        "group": "Personal",
+                        "info": { "secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30 }
+                    }
+                ],
+                "groups": [{ "uuid": "6e5cbf34-4aa8-4b18-a76b-1d2e9e3a2f40", "name": "Work" }]
+            }
+        }"#;
+
+        // Act
+        let elements: Vec<OTPElement> = import_from_str::<AegisJson>(json).unwrap();
+
+        // Assert
+        assert_eq!(vec!["Work".to_string()], elements[0].tags);
+        assert_eq!(vec!["Personal".to_string()], elements[1].tags);
+    }
+
+    #[test]
+    fn test_note_is_imported() {
+        // Arrange
+        let json = r#"{
+            "db": {
+                "entries": [
+                    {
+                        "type": "totp",
+                        "name": "label",
+                        "issuer": "issuer",
+                        "note": "Backup codes in the safe",
+                        "info": { "secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30 }
+                    },
+                    {
+                        "type": "totp",
+                        "name": "label",
+                        "issuer": "issuer",
+                        "note": "",
+                        "info": { "secret": "JBSWY3DPEHPK3PXP", "algo": "SHA1", "digits": 6, "period": 30 }
+                    }
+                ]
+            }
+        }"#;
+
+        // Act
+        let elements: Vec<OTPElement> = import_from_str::<AegisJson>(json).unwrap();
+
+        // Assert
+        assert_eq!(
+            Some("Backup codes in the safe"),
+            elements[0].note.as_deref()
+        );
+        assert_eq!(None, elements[1].note);
+    }
+}
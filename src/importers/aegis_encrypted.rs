@@ -6,16 +6,26 @@ use serde::Deserialize;
 use zeroize::Zeroize;
 
 use crate::otp::otp_element::OTPElement;
-use crate::utils;
 use scrypt::{Params, scrypt};
 
 use super::aegis::AegisDb;
 
+/// Aegis backup encrypted with a password, which must be set before the conversion
 #[derive(Deserialize)]
 pub struct AegisEncryptedDatabase {
     //version: u32,
     header: AegisEncryptedHeader,
     db: String,
+    #[serde(skip)]
+    password: String,
+}
+
+impl AegisEncryptedDatabase {
+    /// Sets the password the backup was encrypted with, it is not part of the backup
+    pub fn set_password(&mut self, password: String) {
+        self.password.zeroize();
+        self.password = password;
+    }
 }
 
 #[derive(Deserialize)]
@@ -46,8 +56,8 @@ struct AegisEncryptedSlot {
 impl TryFrom<AegisEncryptedDatabase> for Vec<OTPElement> {
     type Error = String;
 
-    fn try_from(aegis_encrypted: AegisEncryptedDatabase) -> Result<Self, Self::Error> {
-        let mut password = utils::password("Insert your Aegis password: ", 0);
+    fn try_from(mut aegis_encrypted: AegisEncryptedDatabase) -> Result<Self, Self::Error> {
+        let mut password = std::mem::take(&mut aegis_encrypted.password);
         let master_key: Option<Vec<u8>> = get_master_key(&aegis_encrypted, &password);
         password.zeroize();
 
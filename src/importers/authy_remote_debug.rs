@@ -3,8 +3,14 @@ Import from JSON file exported from a script executed from remote debugging.
 For more information see https://gist.github.com/gboudreau/94bb0c11a6209c82418d01a59d958c93
 */
 
-use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType};
+use crate::otp::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPElement},
+    otp_type::OTPType,
+};
 use serde::Deserialize;
+use uuid::Uuid;
 
 const URL_INDEX: usize = 3;
 const PARAMETERS_INDEX: usize = 1;
@@ -19,6 +25,7 @@ struct AuthyExportedJsonElement {
 
 // Newtype pattern to bypass compiler check for impl From for Vec<AuthyExportedJsonElement>
 // https://rust-unofficial.github.io/patterns/patterns/behavioural/newtype.html
+/// Authy codes exported using the remote debugging script
 #[derive(Deserialize)]
 pub struct AuthyExportedList(Vec<AuthyExportedJsonElement>);
 
@@ -85,6 +92,22 @@ impl From<AuthyExportedJsonElement> for OTPElement {
             period: 30,
             counter,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         }
     }
 }
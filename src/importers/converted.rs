@@ -1,6 +1,12 @@
 use serde::Deserialize;
+use uuid::Uuid;
 
-use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType};
+use crate::otp::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPElement},
+    otp_type::OTPType,
+};
 
 #[derive(Deserialize)]
 struct ConvertedJson {
@@ -28,11 +34,28 @@ impl From<ConvertedJson> for OTPElement {
             period: 30,
             counter,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         }
     }
 }
 
 // Newtype pattern to permit impl to Vec<OTPElement>
+/// Codes converted by the cotp scripts from the backups of other apps
 #[derive(Deserialize)]
 pub struct ConvertedJsonList(Vec<ConvertedJson>);
 
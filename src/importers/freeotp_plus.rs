@@ -1,8 +1,15 @@
 use data_encoding::BASE32_NOPAD;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
-use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType};
+use crate::otp::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPElement},
+    otp_type::OTPType,
+};
 
+/// FreeOTP+ backup, also used by the export
 #[derive(Serialize, Deserialize)]
 pub struct FreeOTPPlusJson {
     #[serde(rename = "tokenOrder")]
@@ -61,6 +68,22 @@ impl From<FreeOTPElement> for OTPElement {
             algorithm: OTPAlgorithm::from(token.algo.as_str()),
             period: token.period,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         }
     }
 }
@@ -84,11 +107,18 @@ fn encode_secret(secret: &[i8]) -> String {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    use std::{fs::read_to_string, path::PathBuf};
 
     use crate::{
-        importers::{freeotp_plus::FreeOTPElement, importer::import_from_path},
-        otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType},
+        importers::{freeotp_plus::FreeOTPElement, importer::import_from_str},
+        otp::{
+            code_encoder::CodeEncoder,
+            otp_algorithm::OTPAlgorithm,
+            otp_element::{CustomFields, OTPElement},
+            otp_type::OTPType,
+        },
     };
 
     use std::fs;
@@ -113,9 +143,9 @@ mod tests {
 
     #[test]
     fn test_conversion() {
-        let imported = import_from_path::<FreeOTPPlusJson>(PathBuf::from(
-            "test_samples/freeotp_plus_example1.json",
-        ));
+        let imported = import_from_str::<FreeOTPPlusJson>(
+            &read_to_string("test_samples/freeotp_plus_example1.json").unwrap(),
+        );
 
         assert_eq!(
             vec![
@@ -128,7 +158,23 @@ mod tests {
                     algorithm: OTPAlgorithm::Sha1,
                     period: 30,
                     counter: None,
-                    pin: None
+                    pin: None,
+                    id: Uuid::nil(),
+                    tags: vec![],
+                    note: None,
+                    url: None,
+                    fields: CustomFields::default(),
+                    recovery_codes: vec![],
+                    position: 0,
+                    favorite: false,
+                    created_at: None,
+                    last_used_at: None,
+                    use_count: 0,
+                    archived_at: None,
+                    time_offset_seconds: None,
+                    epoch: None,
+                    ocra_suite: None,
+                    encoder: CodeEncoder::Decimal,
                 },
                 OTPElement {
                     secret: "AAAAAAAA".to_string(),
@@ -139,7 +185,23 @@ mod tests {
                     algorithm: OTPAlgorithm::Sha256,
                     period: 30,
                     counter: None,
-                    pin: None
+                    pin: None,
+                    id: Uuid::nil(),
+                    tags: vec![],
+                    note: None,
+                    url: None,
+                    fields: CustomFields::default(),
+                    recovery_codes: vec![],
+                    position: 0,
+                    favorite: false,
+                    created_at: None,
+                    last_used_at: None,
+                    use_count: 0,
+                    archived_at: None,
+                    time_offset_seconds: None,
+                    epoch: None,
+                    ocra_suite: None,
+                    encoder: CodeEncoder::Decimal,
                 }
             ],
             imported.unwrap()
@@ -12,15 +12,19 @@
 //! the hand-declared message definitions below (Google's schema is stable and
 //! tiny, so no `.proto`/`protoc` build step is needed).
 
-use std::{fs::read_to_string, path::PathBuf};
-
 use base64::{Engine as _, engine::general_purpose};
 use color_eyre::eyre::{Result, eyre};
 use data_encoding::BASE32_NOPAD;
 use prost::Message;
 use url::Url;
+use uuid::Uuid;
 
-use crate::otp::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType};
+use crate::otp::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPElement},
+    otp_type::OTPType,
+};
 
 const MIGRATION_SCHEME: &str = "otpauth-migration";
 
@@ -60,16 +64,9 @@ const ALGORITHM_MD5: i32 = 4;
 const DIGIT_COUNT_EIGHT: i32 = 2;
 const OTP_TYPE_HOTP: i32 = 1;
 
-/// Reads a file containing one or more `otpauth-migration://` URIs and decodes
-/// every OTP parameter contained in them.
-pub fn import_from_google_authenticator(path: PathBuf) -> Result<Vec<OTPElement>> {
-    let content = read_to_string(path)?;
-    import_from_string(&content)
-}
-
 /// Parses every `otpauth-migration://` URI found in `content` (tokens are
 /// split on whitespace, so both single- and multi-line inputs work).
-fn import_from_string(content: &str) -> Result<Vec<OTPElement>> {
+pub fn import_from_string(content: &str) -> Result<Vec<OTPElement>> {
     let uris: Vec<&str> = content
         .split_whitespace()
         .filter(|token| token.starts_with(MIGRATION_SCHEME))
@@ -157,6 +154,22 @@ fn into_otp_element(params: OtpParameters) -> Result<OTPElement> {
         period: 30,
         counter,
         pin: None,
+        id: Uuid::nil(),
+        tags: vec![],
+        note: None,
+        url: None,
+        fields: CustomFields::default(),
+        recovery_codes: vec![],
+        position: 0,
+        favorite: false,
+        created_at: None,
+        last_used_at: None,
+        use_count: 0,
+        archived_at: None,
+        time_offset_seconds: None,
+        epoch: None,
+        ocra_suite: None,
+        encoder: CodeEncoder::Decimal,
     })
 }
 
@@ -223,6 +236,22 @@ mod tests {
                 period: 30,
                 counter: None,
                 pin: None,
+                id: Uuid::nil(),
+                tags: vec![],
+                note: None,
+                url: None,
+                fields: CustomFields::default(),
+                recovery_codes: vec![],
+                position: 0,
+                favorite: false,
+                created_at: None,
+                last_used_at: None,
+                use_count: 0,
+                archived_at: None,
+                time_offset_seconds: None,
+                epoch: None,
+                ocra_suite: None,
+                encoder: CodeEncoder::Decimal,
             }],
             elements
         );
@@ -246,6 +275,22 @@ mod tests {
                 period: 30,
                 counter: Some(42),
                 pin: None,
+                id: Uuid::nil(),
+                tags: vec![],
+                note: None,
+                url: None,
+                fields: CustomFields::default(),
+                recovery_codes: vec![],
+                position: 0,
+                favorite: false,
+                created_at: None,
+                last_used_at: None,
+                use_count: 0,
+                archived_at: None,
+                time_offset_seconds: None,
+                epoch: None,
+                ocra_suite: None,
+                encoder: CodeEncoder::Decimal,
             }],
             elements
         );
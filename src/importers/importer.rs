@@ -1,18 +1,49 @@
-use std::{fmt::Debug, fs::read_to_string, path::PathBuf};
+use std::{
+    fmt::Debug,
+    fs::{read, read_to_string},
+    path::Path,
+};
 
 use color_eyre::eyre::{Result, eyre};
 use serde::Deserialize;
 
-use crate::otp::otp_element::OTPElement;
+use crate::{crypto::public_key::decrypt_with_identity_file, otp::otp_element::OTPElement};
+
+/// Reads the backup file, decrypting it first if an age identity file is given
+pub fn read_backup(path: &Path, identity: Option<&Path>) -> Result<String> {
+    match identity {
+        Some(identity) => {
+            let decrypted = decrypt_with_identity_file(&read(path)?, identity)?;
+            String::from_utf8(decrypted).map_err(|e| eyre!("Invalid UTF-8 backup: {e}"))
+        }
+        None => Ok(read_to_string(path)?),
+    }
+}
 
 /// Common flow for all the importers
-pub fn import_from_path<T>(path: PathBuf) -> Result<Vec<OTPElement>>
+pub fn import_from_str<T>(json: &str) -> Result<Vec<OTPElement>>
 where
     T: for<'a> Deserialize<'a> + TryInto<Vec<OTPElement>>,
     <T as TryInto<Vec<OTPElement>>>::Error: Debug,
 {
-    let json = read_to_string(path)?;
-    let deserialized: T = serde_json::from_str(json.as_str()).map_err(|e| {
+    convert_backup(deserialize_backup::<T>(json)?)
+}
+
+/// Converts an already deserialized backup into cotp elements
+pub fn convert_backup<T>(backup: T) -> Result<Vec<OTPElement>>
+where
+    T: TryInto<Vec<OTPElement>>,
+    <T as TryInto<Vec<OTPElement>>>::Error: Debug,
+{
+    backup.try_into().map_err(|e| eyre!("{:?}", e))
+}
+
+/// Deserializes the backup, so that it can be customized before the conversion
+pub fn deserialize_backup<T>(json: &str) -> Result<T>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    serde_json::from_str(json).map_err(|e| {
         eyre!(
             "Invalid JSON import format.
             Please check the file you are trying to import. For further information please check these guidelines:
@@ -21,7 +52,5 @@ where
             Specific error: {:?}",
             e
         )
-    })?;
-    let mapped: Vec<OTPElement> = deserialized.try_into().map_err(|e| eyre!("{:?}", e))?;
-    Ok(mapped)
+    })
 }
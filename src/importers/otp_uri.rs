@@ -17,11 +17,15 @@ impl TryFrom<OtpUriList> for Vec<OTPElement> {
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use uuid::Uuid;
+
+    use std::fs::read_to_string;
 
     use crate::{
-        exporters::otp_uri::OtpUriList, importers::importer::import_from_path,
-        otp::otp_element::OTPElement,
+        exporters::otp_uri::OtpUriList,
+        importers::importer::import_from_str,
+        otp::code_encoder::CodeEncoder,
+        otp::otp_element::{CustomFields, OTPElement},
     };
 
     #[test]
@@ -37,12 +41,28 @@ mod tests {
             period: 30,
             counter: None,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         };
 
         // Act
-        let mut imported = import_from_path::<OtpUriList>(PathBuf::from(
-            "test_samples/otp_uri/input_otp_uri.json",
-        ))
+        let mut imported = import_from_str::<OtpUriList>(
+            &read_to_string("test_samples/otp_uri/input_otp_uri.json").unwrap(),
+        )
         .unwrap();
 
         // Assert
@@ -4,6 +4,7 @@ use crate::interface::enums::Focus;
 use crate::interface::enums::Page;
 use crate::interface::enums::Page::{Main, Qrcode};
 use crate::otp::otp_element::OTPDatabase;
+use crate::otp::otp_type::OTPType;
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout};
@@ -11,7 +12,8 @@ use ratatui::style::{Color, Modifier, Style};
 use ratatui::widgets::{Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Table, Wrap};
 
 use crate::interface::stateful_table::{StatefulTable, fill_table};
-use crate::utils::percentage;
+use crate::path::current_vault;
+use crate::utils::{current_timestamp, format_date, percentage};
 
 use super::enums::PopupAction;
 use super::popup::centered_rect;
@@ -31,6 +33,8 @@ pub struct App<'a> {
     pub(crate) table: StatefulTable,
     pub(crate) database: &'a mut OTPDatabase,
     progress: u16,
+    /// Unix timestamp of the last time the codes were generated
+    refreshed_at: u64,
     /// Text to print replacing the percentage
     pub(crate) label_text: String,
     pub(crate) print_percentage: bool,
@@ -41,6 +45,12 @@ pub struct App<'a> {
 
     /// Info text in the `QRCode` page
     pub(crate) qr_code_page_label: &'static str,
+    /// Vaults listed in the vault switcher
+    pub(crate) vaults: Vec<String>,
+    /// Tags listed in the tag filter
+    pub(crate) tags: Vec<String>,
+    /// Vault to open once the application is closed
+    pub(crate) switch_vault: Option<String>,
 }
 
 pub struct Popup {
@@ -57,12 +67,17 @@ impl<'a> App<'a> {
         title.push_str(" v");
         // Settings cotp version from env var defined in build.rs
         title.push_str(env!("COTP_VERSION"));
+        if let Some(vault) = current_vault() {
+            title.push_str(" - ");
+            title.push_str(&vault);
+        }
         Self {
             running: true,
             title,
             table: StatefulTable::new(database.elements_ref()),
             database,
             progress: percentage(),
+            refreshed_at: current_timestamp(),
             label_text: String::new(),
             print_percentage: true,
             current_page: Page::default(),
@@ -75,6 +90,9 @@ impl<'a> App<'a> {
                 percent_y: 20,
             },
             qr_code_page_label: DEFAULT_QRCODE_LABEL,
+            vaults: vec![],
+            tags: vec![],
+            switch_vault: None,
         }
     }
 
@@ -87,14 +105,14 @@ impl<'a> App<'a> {
     /// Handles the tick event of the terminal.
     pub fn tick(&mut self, force_update: bool) {
         // Update progress bar
-        let new_progress = percentage();
-        // Check for new cycle
-        if force_update || new_progress < self.progress {
-            // Update codes
+        self.progress = percentage();
+        // Update codes every second, so the ones with any period and the next ones are shown in time
+        let now = current_timestamp();
+        if force_update || now != self.refreshed_at {
             self.table.items.clear();
             fill_table(&mut self.table, self.database.elements_ref());
+            self.refreshed_at = now;
         }
-        self.progress = new_progress;
     }
 
     /// Renders the user interface widgets.
@@ -108,8 +126,7 @@ impl<'a> App<'a> {
     fn render_qrcode_page(&self, frame: &mut Frame<'_>) {
         let paragraph = self
             .table
-            .state
-            .selected()
+            .selected_index()
             .and_then(|index| self.database.elements_ref().get(index))
             .map_or_else(
                 || {
@@ -250,12 +267,16 @@ impl<'a> App<'a> {
             Constraint::Percentage(25),
         ];
 
+        let title = match &self.table.tag {
+            Some(tag) => format!("{} [{tag}]", self.title),
+            None => self.title.clone(),
+        };
         let t = Table::new(rows, TABLE_WIDTHS)
             .header(header)
             .block(
                 Block::default()
                     .borders(Borders::TOP | Borders::BOTTOM)
-                    .title(self.title.as_str()),
+                    .title(title),
             )
             .row_highlight_style(
                 Style::default()
@@ -267,19 +288,26 @@ impl<'a> App<'a> {
 
         let selected_element = self
             .table
-            .state
-            .selected()
+            .selected_index()
             .and_then(|i| self.database.get_element(i));
 
         let mut text = if let Some(element) = selected_element {
-            format!(
+            let mut text = format!(
                 "
             Type: {}
             Algorithm: {}
             Period: {} {}
+            Time offset: {}
             Counter: {}
             Pin: {}
-            ",
+            Tags: {}
+            Recovery codes: {}
+            Created: {}
+            Last used: {}
+            Used: {} times
+            URL: {}
+            Note: {}
+            {}",
                 element.type_,
                 element.algorithm,
                 element.period,
@@ -288,11 +316,48 @@ impl<'a> App<'a> {
                 } else {
                     "seconds"
                 },
+                element
+                    .time_offset_seconds
+                    .map_or_else(|| String::from("N/A"), |s| format!("{s:+} seconds")),
                 element
                     .counter
                     .map_or_else(|| String::from("N/A"), |e| e.to_string()),
-                element.pin.clone().unwrap_or_else(|| String::from("N/A"))
-            )
+                element.pin.clone().unwrap_or_else(|| String::from("N/A")),
+                if element.tags.is_empty() {
+                    String::from("N/A")
+                } else {
+                    element.tags.join(", ")
+                },
+                if element.recovery_codes.is_empty() {
+                    String::from("N/A")
+                } else {
+                    format!(
+                        "{} of {} unused",
+                        element.unused_recovery_codes(),
+                        element.recovery_codes.len()
+                    )
+                },
+                element
+                    .created_at
+                    .map_or_else(|| String::from("N/A"), format_date),
+                element
+                    .last_used_at
+                    .map_or_else(|| String::from("Never"), format_date),
+                element.use_count,
+                element.url.clone().unwrap_or_else(|| String::from("N/A")),
+                element.note.clone().unwrap_or_else(|| String::from("N/A")),
+                element
+                    .fields
+                    .iter()
+                    .map(|(name, value)| format!("{name}: {value}\n"))
+                    .collect::<String>()
+            );
+            if element.type_ == OTPType::Blizzard
+                && let Ok(restore_code) = element.blizzard_restore_code()
+            {
+                text.push_str(format!("Restore code: {restore_code}\n").as_str());
+            }
+            text
         } else {
             String::new()
         };
@@ -11,6 +11,8 @@ pub enum PopupAction {
     DeleteOtp,
     GeneralInfo,
     SaveBeforeQuit,
+    SwitchVault,
+    FilterTag,
 }
 
 #[derive(Eq, PartialEq, Debug, Default)]
@@ -10,10 +10,11 @@ use crate::{
         app::{App, Popup},
         enums::{Focus, Page, PopupAction},
     },
-    otp::otp_type::OTPType,
+    otp::{history::HistoryAction, otp_type::OTPType},
+    path::list_vaults,
 };
 
-use super::{handle_exit, show_popup};
+use super::{MAX_POPUP_CHOICES, handle_exit, show_popup};
 
 pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
     match key_event.code {
@@ -30,7 +31,9 @@ pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
                 // Ask the user if he wants to delete the OTP Code
                 show_popup(
                     Popup {
-                        text: String::from("Do you want to delete the selected OTP Code? [Y/N]"),
+                        text: String::from(
+                            "Do you want to move the selected OTP Code to the trash? [Y/N]",
+                        ),
                         percent_x: 60,
                         percent_y: 20,
                         action: PopupAction::DeleteOtp,
@@ -40,6 +43,12 @@ pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
             }
         }
 
+        // Move the selected code in the user defined order
+        KeyCode::Up if key_event.modifiers == KeyModifiers::SHIFT => move_selected(app, true),
+        KeyCode::Char('K') => move_selected(app, true),
+        KeyCode::Down if key_event.modifiers == KeyModifiers::SHIFT => move_selected(app, false),
+        KeyCode::Char('J') => move_selected(app, false),
+
         // Move into the table
         KeyCode::Up | KeyCode::Char('k') => {
             app.print_percentage = true;
@@ -65,15 +74,26 @@ pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
 
         KeyCode::Char(' ') => handle_switch_page(app, Qrcode),
 
+        KeyCode::Char('v' | 'V') => show_vault_switcher(app),
+
+        KeyCode::Char('t' | 'T') => show_tag_filter(app),
+
         KeyCode::Char('?') => {
             let info_text = String::from(
                 "
             Press:
-            d -> Delete selected code
+            d -> Move the selected code to the trash
             + -> Increment the HOTP counter
             - -> Decrement the HOTP counter
             Space -> Show QRCode of the selected element
             Enter -> Copy the OTP Code to the clipboard
+            v -> Switch vault
+            t -> Filter codes by tag
+            u -> Undo the last change
+            f -> Mark the selected code as favorite
+            F -> Show the favorite codes first
+            Shift-Up | K -> Move the selected code up
+            Shift-Down | J -> Move the selected code down
             CTRL-F | '/' -> Search codes
             CTRL-W | CTRL-U -> Clear the search query
             q, CTRL-D, Esc -> Exit the application
@@ -96,6 +116,26 @@ pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
 
         KeyCode::Char('/') => app.focus = Focus::SearchBar,
 
+        KeyCode::Char('f') => toggle_favorite(app),
+
+        KeyCode::Char('u') => {
+            app.label_text = match app.database.undo() {
+                Ok(entry) => format!("Undone: {entry}"),
+                Err(e) => e,
+            };
+            app.print_percentage = false;
+            app.tick(true);
+        }
+
+        KeyCode::Char('F') => {
+            let selected = app.table.selected_index();
+            app.table.favorites_first = !app.table.favorites_first;
+            app.tick(true);
+            if let Some(index) = selected {
+                app.table.select_index(index);
+            }
+        }
+
         KeyCode::Enter => match app.current_page {
             Main => {
                 app.label_text = copy_selected_code_to_clipboard(app);
@@ -104,8 +144,7 @@ pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
             Qrcode => {
                 let selected_element = app
                     .table
-                    .state
-                    .selected()
+                    .selected_index()
                     .and_then(|index| app.database.elements_ref().get(index));
 
                 if let Some(element) = selected_element {
@@ -120,10 +159,16 @@ pub(super) fn main_handler(key_event: KeyEvent, app: &mut App) {
 }
 
 fn handle_counter_switch(app: &mut App, increment: bool) {
-    if let Some(selected) = app.table.state.selected()
-        && let Some(element) = app.database.mut_element(selected)
-        && element.type_ == OTPType::Hotp
+    if let Some(selected) = app.table.selected_index()
+        && app
+            .database
+            .get_element(selected)
+            .is_some_and(|e| e.type_ == OTPType::Hotp)
     {
+        app.database
+            .record_change(selected, HistoryAction::CounterChange);
+        // safe to unwrap because the element exists
+        let element = app.database.mut_element(selected).unwrap();
         // safe to unwrap because the element type is HOTP
         let counter = element.counter.unwrap();
         element.counter = if increment {
@@ -136,6 +181,68 @@ fn handle_counter_switch(app: &mut App, increment: bool) {
     }
 }
 
+fn toggle_favorite(app: &mut App) {
+    if let Some(selected) = app.table.selected_index()
+        && let Some(element) = app.database.mut_element(selected)
+    {
+        element.favorite = !element.favorite;
+        app.database.mark_modified();
+        app.tick(true);
+        app.table.select_index(selected);
+    }
+}
+
+/// Exchanges the selected code with the one shown above or below it
+fn move_selected(app: &mut App, up: bool) {
+    if let Some(selected) = app.table.selected_index()
+        && let Some(neighbour) = app.table.neighbour_index(up)
+    {
+        app.database.swap_positions(selected, neighbour);
+        app.tick(true);
+        app.table.select_index(selected);
+    }
+}
+
+fn show_vault_switcher(app: &mut App) {
+    app.vaults = list_vaults();
+    let mut text = String::from("Press the number of the vault to open:\n\n");
+    app.vaults
+        .iter()
+        .take(MAX_POPUP_CHOICES)
+        .enumerate()
+        .for_each(|(i, vault)| text.push_str(&format!("{} -> {vault}\n", i + 1)));
+    text.push_str("\nEsc -> Cancel");
+    show_popup(
+        Popup {
+            text,
+            percent_x: 40,
+            percent_y: 50,
+            action: PopupAction::SwitchVault,
+        },
+        app,
+    );
+}
+
+fn show_tag_filter(app: &mut App) {
+    app.tags = app.database.tags();
+    let mut text = String::from("Press the number of the tag to show:\n\n0 -> All codes\n");
+    app.tags
+        .iter()
+        .take(MAX_POPUP_CHOICES)
+        .enumerate()
+        .for_each(|(i, tag)| text.push_str(&format!("{} -> {tag}\n", i + 1)));
+    text.push_str("\nEsc -> Cancel");
+    show_popup(
+        Popup {
+            text,
+            percent_x: 40,
+            percent_y: 50,
+            action: PopupAction::FilterTag,
+        },
+        app,
+    );
+}
+
 fn handle_switch_page(app: &mut App, page: Page) {
     if app.current_page == page {
         app.reset();
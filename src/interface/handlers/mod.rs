@@ -13,6 +13,9 @@ mod main_window;
 mod popup;
 mod search_bar;
 
+/// Vaults and tags can be selected in the popups using the numeric keys
+const MAX_POPUP_CHOICES: usize = 9;
+
 /// Handles the key events and updates the state of [`App`].
 pub fn handle_key_events(key_event: KeyEvent, app: &mut App) {
     match app.focus {
@@ -43,10 +46,13 @@ pub(crate) fn copy_selected_code_to_clipboard(app: &mut App) -> String {
         Some(selected) => match app.table.items.get(selected) {
             Some(element) => match element.values.get(3) {
                 Some(otp_code) => match copy_string_to_clipboard(otp_code) {
-                    Ok(result) => match result {
-                        CopyType::Native => "Copied!".to_string(),
-                        CopyType::OSC52 => "Remote copied!".to_string(),
-                    },
+                    Ok(result) => {
+                        app.database.record_use(element.index);
+                        match result {
+                            CopyType::Native => "Copied!".to_string(),
+                            CopyType::OSC52 => "Remote copied!".to_string(),
+                        }
+                    }
                     _ => "Cannot copy".to_string(),
                 },
                 None => "Cannot get OTP Code column".to_string(),
@@ -5,6 +5,8 @@ use crate::interface::{
     enums::{Focus, PopupAction},
 };
 
+use super::{MAX_POPUP_CHOICES, handle_exit};
+
 pub(super) fn popup_handler(key_event: KeyEvent, app: &mut App) {
     match app.popup.action {
         PopupAction::EditOtp => todo!(),
@@ -34,9 +36,48 @@ pub(super) fn popup_handler(key_event: KeyEvent, app: &mut App) {
                 app.running = false;
             }
             KeyCode::Char('n' | 'N') => {
-                app.database.needs_modification = false;
+                app.database.discard_modifications();
                 app.running = false;
             }
+            KeyCode::Esc => {
+                app.switch_vault = None;
+                app.focus = Focus::MainPage;
+            }
+            _ => {}
+        },
+        PopupAction::SwitchVault => match key_event.code {
+            KeyCode::Char(c @ '1'..='9') => {
+                let index = c as usize - '1' as usize;
+                if let Some(vault) = app.vaults.get(index).filter(|_| index < MAX_POPUP_CHOICES) {
+                    app.switch_vault = Some(vault.clone());
+                    app.focus = Focus::MainPage;
+                    handle_exit(app);
+                }
+            }
+            KeyCode::Esc => {
+                app.focus = Focus::MainPage;
+            }
+            _ => {}
+        },
+        PopupAction::FilterTag => match key_event.code {
+            KeyCode::Char(c @ '0'..='9') => {
+                let tag = match c {
+                    '0' => None,
+                    _ => {
+                        let index = c as usize - '1' as usize;
+                        match app.tags.get(index).filter(|_| index < MAX_POPUP_CHOICES) {
+                            Some(tag) => Some(tag.clone()),
+                            None => return,
+                        }
+                    }
+                };
+                app.table.tag = tag;
+                app.tick(true);
+                app.table
+                    .state
+                    .select((!app.table.items.is_empty()).then_some(0));
+                app.focus = Focus::MainPage;
+            }
             KeyCode::Esc => {
                 app.focus = Focus::MainPage;
             }
@@ -46,21 +87,16 @@ pub(super) fn popup_handler(key_event: KeyEvent, app: &mut App) {
 }
 
 fn delete_selected_code(app: &mut App) -> Result<String, String> {
-    match app.table.state.selected() {
-        Some(selected) => {
-            if app.database.elements_ref().len() > selected {
-                app.database.delete_element(selected);
-                app.table.items.remove(selected);
-                if selected >= app.database.elements_ref().len() {
-                    app.table.previous();
-                } else if app.database.elements_ref().is_empty() {
-                    app.table.state.select(None);
-                }
-                Ok("Done".to_string())
-            } else {
-                Err("Index out of bounds".to_string())
+    match (app.table.state.selected(), app.table.selected_index()) {
+        (Some(row), Some(index)) => {
+            app.database.archive_element(index)?;
+            app.table.items.remove(row);
+            if row >= app.table.items.len() {
+                app.table.previous();
             }
+            Ok("Done".to_string())
         }
-        None => Err("No code selected".to_string()),
+        (Some(_), None) => Err("Index out of bounds".to_string()),
+        (None, _) => Err("No code selected".to_string()),
     }
 }
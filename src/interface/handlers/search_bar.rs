@@ -45,10 +45,9 @@ fn search_and_select(app: &mut App) {
     // Check for issuer
     for iter in app.table.items.iter().enumerate() {
         let (index, row) = iter;
-        if row
-            .values
-            .get(1)
-            .unwrap()
+        // The rendered issuer is prefixed by a marker for the favorite codes
+        if app.database.elements_ref()[row.index]
+            .issuer
             .to_lowercase()
             .starts_with(&app.search_query.to_lowercase())
         {
@@ -73,10 +72,8 @@ fn search_and_select(app: &mut App) {
     // Check if issuer contains the query
     for iter in app.table.items.iter().enumerate() {
         let (index, row) = iter;
-        if row
-            .values
-            .get(1)
-            .unwrap()
+        if app.database.elements_ref()[row.index]
+            .issuer
             .to_lowercase()
             .contains(&app.search_query.to_lowercase())
         {
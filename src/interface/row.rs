@@ -3,13 +3,27 @@ use ratatui::style::Style;
 use ratatui::widgets::Cell;
 
 pub(crate) struct Row {
+    /// Position of the displayed code in the database
+    pub(crate) index: usize,
     pub(crate) values: Vec<String>,
+    /// Code following the current one, shown next to it when it is about to expire
+    pub(crate) next_code: Option<String>,
     has_error: bool,
 }
 
 impl Row {
-    pub(crate) fn new(values: Vec<String>, has_error: bool) -> Self {
-        Row { values, has_error }
+    pub(crate) fn new(index: usize, values: Vec<String>, has_error: bool) -> Self {
+        Row {
+            index,
+            values,
+            next_code: None,
+            has_error,
+        }
+    }
+
+    pub(crate) fn with_next_code(mut self, next_code: Option<String>) -> Self {
+        self.next_code = next_code;
+        self
     }
     pub fn height(&self) -> u16 {
         (self
@@ -22,15 +36,21 @@ impl Row {
     }
 
     pub fn cells(&self) -> Vec<Cell<'_>> {
+        let last = self.values.len().saturating_sub(1);
         self.values
             .iter()
-            .map(|c| {
+            .enumerate()
+            .map(|(i, c)| {
                 let style = if self.has_error {
                     Style::default().bg(Yellow).fg(Black)
                 } else {
                     Style::default()
                 };
-                Cell::from(c.as_str()).style(style)
+                match &self.next_code {
+                    Some(next_code) if i == last => Cell::from(format!("{c} → {next_code}")),
+                    _ => Cell::from(c.as_str()),
+                }
+                .style(style)
             })
             .collect()
     }
@@ -1,11 +1,24 @@
 use crate::interface::row::Row;
 use ratatui::widgets::TableState;
 
-use crate::otp::{otp_element::OTPElement, otp_type::OTPType};
+use crate::otp::{
+    otp_element::OTPElement,
+    otp_type::OTPType,
+    sort_order::{SortOrder, sorted_indexes},
+};
+use crate::utils::current_timestamp;
+
+const FAVORITE_MARKER: &str = "★ ";
+/// Seconds before the expiration of a code when the next one is shown too
+const NEXT_CODE_THRESHOLD: u64 = 5;
 
 pub struct StatefulTable {
     pub(crate) state: TableState,
     pub(crate) items: Vec<Row>,
+    /// Only show the codes having this tag
+    pub(crate) tag: Option<String>,
+    /// Show the favorite codes before the other ones
+    pub(crate) favorites_first: bool,
 }
 
 impl StatefulTable {
@@ -13,10 +26,38 @@ impl StatefulTable {
         let mut table = StatefulTable {
             state: TableState::default(),
             items: vec![],
+            tag: None,
+            favorites_first: false,
         };
         fill_table(&mut table, elements);
         table
     }
+    /// Returns the position in the database of the selected code
+    pub fn selected_index(&self) -> Option<usize> {
+        self.state
+            .selected()
+            .and_then(|i| self.items.get(i))
+            .map(|row| row.index)
+    }
+
+    /// Selects the row showing the code at the given position in the database
+    pub fn select_index(&mut self, index: usize) {
+        if let Some(row) = self.items.iter().position(|row| row.index == index) {
+            self.state.select(Some(row));
+        }
+    }
+
+    /// Returns the position in the database of the code shown next to the selected one
+    pub fn neighbour_index(&self, up: bool) -> Option<usize> {
+        let selected = self.state.selected()?;
+        let row = if up {
+            selected.checked_sub(1)?
+        } else {
+            selected + 1
+        };
+        self.items.get(row).map(|row| row.index)
+    }
+
     pub fn next(&mut self) {
         let selected = if self.items.is_empty() {
             None
@@ -57,7 +98,18 @@ impl StatefulTable {
 }
 
 pub fn fill_table(table: &mut StatefulTable, elements: &[OTPElement]) {
-    for (i, element) in elements.iter().enumerate() {
+    let tag = table.tag.clone();
+    let now = current_timestamp();
+    for (i, element) in sorted_indexes(elements, SortOrder::Custom, table.favorites_first)
+        .into_iter()
+        .map(|i| (i, &elements[i]))
+        .filter(|(_, e)| {
+            !e.is_archived()
+                && tag
+                    .as_ref()
+                    .is_none_or(|t| e.has_tags(std::slice::from_ref(t)))
+        })
+    {
         let label = match element.type_ {
             OTPType::Hotp => match element.counter {
                 Some(result) => element.label.clone() + (format!(" ({result} counter)").as_str()),
@@ -65,20 +117,37 @@ pub fn fill_table(table: &mut StatefulTable, elements: &[OTPElement]) {
             },
             _ => element.label.clone(),
         };
-        let result = element.get_otp_code();
+        let result = element.get_otp_code_at(now);
 
         let error = result.is_err();
-        table.items.push(Row::new(
-            vec![
-                (i + 1).to_string(),
-                element.issuer.clone(),
-                label,
-                match result {
-                    Ok(code) => code,
-                    Err(e) => e.to_string(),
-                },
-            ],
-            error,
-        ));
+        let next_code = next_code(element, now).filter(|_| !error);
+        table.items.push(
+            Row::new(
+                i,
+                vec![
+                    (i + 1).to_string(),
+                    if element.favorite {
+                        format!("{FAVORITE_MARKER}{}", element.issuer)
+                    } else {
+                        element.issuer.clone()
+                    },
+                    label,
+                    match result {
+                        Ok(code) => code,
+                        Err(e) => e.to_string(),
+                    },
+                ],
+                error,
+            )
+            .with_next_code(next_code),
+        );
     }
 }
+
+/// Returns the next code if the current one is about to expire
+fn next_code(element: &OTPElement, now: u64) -> Option<String> {
+    element
+        .seconds_to_next_code(now)
+        .filter(|seconds| *seconds <= NEXT_CODE_THRESHOLD)
+        .and_then(|seconds| element.get_otp_code_at(now + seconds).ok())
+}
@@ -0,0 +1,51 @@
+//! Library behind the cotp command line authenticator.
+//!
+//! It reads and writes the encrypted cotp databases, generates the codes they contain
+//! and converts them from and to the backups of other authenticator apps.
+//!
+//! ```no_run
+//! use std::path::Path;
+//!
+//! let path = Path::new("db.cotp");
+//! let (mut database, key, salt) = cotp::open_database(path, "password")?;
+//! for element in database.elements_ref() {
+//!     println!("{} {}", element.label, element.get_otp_code()?);
+//! }
+//! database.save_to(path, &key, &salt)?;
+//! # Ok::<(), color_eyre::Report>(())
+//! ```
+#![forbid(unsafe_code)]
+#![warn(missing_docs)]
+// The command line interface lives in the binary, which also compiles these modules and uses
+// the crate private items the library does not need
+#![allow(dead_code)]
+
+pub(crate) mod crypto;
+pub(crate) mod exporters;
+pub(crate) mod importers;
+pub(crate) mod otp;
+pub(crate) mod password;
+pub(crate) mod path;
+pub(crate) mod reading;
+pub(crate) mod utils;
+
+pub use crypto::encrypted_database::{KdfAlgorithm, KdfParams};
+pub use exporters::{do_export, otp_uri::OtpUriList};
+pub use importers::{
+    aegis::AegisJson,
+    aegis_encrypted::AegisEncryptedDatabase,
+    authy_remote_debug::AuthyExportedList,
+    converted::ConvertedJsonList,
+    freeotp_plus::FreeOTPPlusJson,
+    google_authenticator::import_from_string,
+    importer::{convert_backup, deserialize_backup, import_from_str, read_backup},
+};
+pub use otp::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPDatabase, OTPElement, OTPElementBuilder},
+    otp_error::OtpError,
+    otp_type::OTPType,
+    recovery_code::RecoveryCode,
+};
+pub use reading::{ReadResult, create_database, open_database};
@@ -1,19 +1,22 @@
 #![forbid(unsafe_code)]
-use arguments::{CotpArgs, args_parser};
+use crate::arguments::{CotpArgs, CotpSubcommands, args_parser, standalone_parser};
+use crate::crypto::encrypted_database::KdfParams;
+use crate::interface::app::AppResult;
+use crate::interface::event::{Event, EventHandler};
+use crate::interface::handlers::handle_key_events;
+use crate::interface::ui::Tui;
+use crate::otp::otp_element::OTPDatabase;
+use crate::password::PasswordSource;
+use crate::path::{database_path, list_vaults};
+use crate::reading::{ReadResult, get_elements_from_source, get_elements_with_identity};
 use clap::Parser;
 use color_eyre::eyre::eyre;
-use interface::app::AppResult;
-use interface::event::{Event, EventHandler};
-use interface::handlers::handle_key_events;
-use interface::ui::Tui;
-use otp::otp_element::{CURRENT_DATABASE_VERSION, OTPDatabase};
-use path::init_path;
 use ratatui::Terminal;
 use ratatui::prelude::CrosstermBackend;
-use reading::{ReadResult, get_elements_from_input, get_elements_from_stdin};
-use std::{io, vec};
+use std::io::{self, IsTerminal};
 use zeroize::Zeroize;
 
+// The library modules are compiled again here, together with the command line interface
 mod arguments;
 mod clipboard;
 mod crypto;
@@ -21,30 +24,55 @@ mod exporters;
 mod importers;
 mod interface;
 mod otp;
+mod password;
 mod path;
 mod reading;
 mod utils;
 
+/// Initializes an empty database file in the current database path
+pub(crate) fn create_database(
+    password_source: &PasswordSource,
+    kdf: KdfParams,
+) -> color_eyre::Result<ReadResult> {
+    let mut pw = password_source.read_new("Choose a password: ", 8)?;
+    let result = reading::create_database(&database_path(), &pw, kdf);
+    pw.zeroize();
+    result
+}
+
+fn init_app() -> Result<bool, ()> {
+    let db_path = database_path();
+    let db_dir = db_path.parent().unwrap();
+    if !db_dir.exists() {
+        if let Err(_e) = std::fs::create_dir_all(db_dir) {
+            return Err(());
+        }
+        return Ok(true);
+    }
+    Ok(!db_path.exists())
+}
+
 fn init(args: &CotpArgs) -> color_eyre::Result<ReadResult> {
-    init_path(args);
+    args.init_path()?;
 
-    match utils::init_app() {
+    match init_app() {
         Ok(first_run) => {
             if first_run {
-                // Let's initialize the database file
-                let mut pw = utils::verified_password("Choose a password: ", 8);
-                let mut database = OTPDatabase {
-                    version: CURRENT_DATABASE_VERSION,
-                    elements: vec![],
-                    ..Default::default()
-                };
-                let save_result = database.save_with_pw(&pw);
-                pw.zeroize();
-                save_result.map(|(key, salt)| (database, key, salt.to_vec()))
-            } else if args.password_from_stdin {
-                get_elements_from_stdin()
+                // Only create the database interactively, scripts must use "cotp init"
+                if args.identity.is_some()
+                    || args.password_source()? != PasswordSource::Prompt
+                    || !io::stdin().is_terminal()
+                {
+                    return Err(eyre!(
+                        "Database not found in {}, create it using \"cotp init\"",
+                        database_path().display()
+                    ));
+                }
+                create_database(&PasswordSource::Prompt, KdfParams::default())
+            } else if let Some(identity) = &args.identity {
+                get_elements_with_identity(identity)
             } else {
-                get_elements_from_input()
+                get_elements_from_source(&args.password_source()?)
             }
         }
         Err(()) => Err(eyre!("An error occurred during database creation")),
@@ -54,48 +82,77 @@ fn init(args: &CotpArgs) -> color_eyre::Result<ReadResult> {
 fn main() -> AppResult<()> {
     color_eyre::install()?;
 
-    let cotp_args: CotpArgs = CotpArgs::parse();
-    let (database, mut key, salt) = match init(&cotp_args) {
-        Ok(v) => v,
-        Err(e) => {
-            println!("{e}");
-            std::process::exit(-1);
-        }
-    };
-
-    let mut reowned_database = match args_parser(cotp_args, database) {
-        Ok(d) => d,
-        Err(e) => {
+    let mut cotp_args: CotpArgs = CotpArgs::parse();
+    if let Some(result) = standalone_parser(&cotp_args) {
+        if let Err(e) = result {
             eprintln!("An error occurred: {e}");
-            key.zeroize();
             std::process::exit(-2)
         }
+        std::process::exit(0)
+    }
+
+    // The other subcommands have been run by standalone_parser
+    let mut command = match cotp_args.command.take() {
+        Some(CotpSubcommands::Database(command)) => Some(*command),
+        _ => None,
     };
+    loop {
+        let (database, mut key, salt) = match init(&cotp_args) {
+            Ok(v) => v,
+            Err(e) => {
+                println!("{e}");
+                std::process::exit(-1);
+            }
+        };
 
-    let error_code = if reowned_database.is_modified() {
-        match reowned_database.save(&key, &salt) {
-            Ok(()) => {
-                println!("Modifications have been persisted");
-                0
+        let (mut reowned_database, switch_vault) = match args_parser(command.take(), database) {
+            Ok(d) => d,
+            Err(e) => {
+                eprintln!("An error occurred: {e}");
+                key.zeroize();
+                std::process::exit(-2)
             }
-            _ => {
-                eprintln!("An error occurred during database overwriting");
-                -1
+        };
+
+        let error_code = if reowned_database.needs_saving() {
+            // Do not print anything if only bookkeeping data changed, like after "cotp extract"
+            let modified = reowned_database.is_modified();
+            match reowned_database.save(&key, &salt) {
+                Ok(()) => {
+                    if modified {
+                        println!("Modifications have been persisted");
+                    }
+                    0
+                }
+                // Usage statistics are best effort, they must not make read only commands fail
+                Err(_) if !modified => 0,
+                _ => {
+                    eprintln!("An error occurred during database overwriting");
+                    -1
+                }
             }
+        } else {
+            0
+        };
+        key.zeroize();
+
+        // The user asked to switch vault from the dashboard, so open it
+        match switch_vault {
+            Some(vault) if error_code == 0 => cotp_args.switch_vault(vault),
+            _ => std::process::exit(error_code),
         }
-    } else {
-        0
-    };
-    key.zeroize();
-    std::process::exit(error_code)
+    }
 }
 
-fn dashboard(mut database: OTPDatabase) -> AppResult<OTPDatabase> {
-    if database.elements_ref().is_empty() {
+/// Runs the dashboard, returning the vault the user wants to switch to, if any
+pub(crate) fn dashboard(mut database: OTPDatabase) -> AppResult<(OTPDatabase, Option<String>)> {
+    let mut switch_vault = None;
+    // An empty vault still opens the dashboard if there are other vaults to switch to
+    if database.elements_ref().is_empty() && list_vaults().len() < 2 {
         println!("No codes, type \"cotp -h\" to get help");
     } else {
         // Create an application.
-        let mut app = interface::app::App::new(&mut database);
+        let mut app = crate::interface::app::App::new(&mut database);
 
         // Initialize the terminal user interface.
         let backend = CrosstermBackend::new(io::stderr());
@@ -122,7 +179,8 @@ fn dashboard(mut database: OTPDatabase) -> AppResult<OTPDatabase> {
 
         // Exit the user interface.
         tui.exit()?;
+        switch_vault = app.switch_vault;
     }
 
-    Ok(database)
+    Ok((database, switch_vault))
 }
@@ -0,0 +1,89 @@
+// Battle.net authenticator, see https://github.com/winauth/winauth/blob/master/Authenticator/BattleNetAuthenticator.cs
+
+use sha1::{Digest, Sha1};
+
+use crate::otp::otp_algorithm::OTPAlgorithm;
+use crate::otp::otp_element::format_code;
+use crate::otp::otp_error::OtpError;
+
+use super::{hotp_maker::decode_secret, totp_maker::totp};
+
+pub const BLIZZARD_DIGITS: u64 = 8;
+pub const BLIZZARD_PERIOD: u64 = 30;
+const RESTORE_CODE_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRTUVWXYZ";
+const RESTORE_CODE_LENGTH: usize = 10;
+
+pub fn blizzard(secret: &str, time: u64) -> Result<String, OtpError> {
+    let code = totp(secret, OTPAlgorithm::Sha1, BLIZZARD_PERIOD, 0, time)?;
+    format_code(BLIZZARD_DIGITS, code)
+}
+
+/// Formats a serial like US-1234-5678-9012, made of the region and 12 digits
+pub fn normalize_serial(serial: &str) -> Result<String, OtpError> {
+    let compact: String = serial
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    let (region, digits) = compact.split_at(compact.len().min(2));
+    if !region.chars().all(|c| c.is_ascii_alphabetic())
+        || digits.len() != 12
+        || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return Err(OtpError::InvalidSerial(serial.to_string()));
+    }
+    Ok(format!(
+        "{region}-{}-{}-{}",
+        &digits[0..4],
+        &digits[4..8],
+        &digits[8..12]
+    ))
+}
+
+/// Computes the restore code, which Battle.net asks to move the authenticator to another device
+pub fn restore_code(serial: &str, secret: &str) -> Result<String, OtpError> {
+    let serial = normalize_serial(serial)?.replace('-', "");
+    let mut hasher = Sha1::new();
+    hasher.update(serial.as_bytes());
+    hasher.update(decode_secret(secret)?);
+    let digest = hasher.finalize();
+    Ok(digest[digest.len() - RESTORE_CODE_LENGTH..]
+        .iter()
+        .map(|b| char::from(RESTORE_CODE_ALPHABET[usize::from(b & 0x1f)]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blizzard, normalize_serial, restore_code};
+
+    // Secret made of the bytes from 0 to 19
+    const SECRET: &str = "AAAQEAYEAUDAOCAJBIFQYDIOB4IBCEQT";
+
+    #[test]
+    fn test_blizzard_code() {
+        // RFC 6238 test vector, Battle.net codes are 8 digits SHA1 TOTP
+        assert_eq!(
+            Ok("94287082".to_string()),
+            blizzard("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", 59)
+        );
+    }
+
+    #[test]
+    fn test_restore_code() {
+        assert_eq!(
+            Ok("H6EP0K8DHE".to_string()),
+            restore_code("us123456789012", SECRET)
+        );
+    }
+
+    #[test]
+    fn test_serial_format() {
+        assert_eq!(
+            Ok("EU-1234-5678-9012".to_string()),
+            normalize_serial("eu 1234 5678 9012")
+        );
+        assert!(normalize_serial("US-1234-5678").is_err());
+        assert!(normalize_serial("12-1234-5678-9012").is_err());
+    }
+}
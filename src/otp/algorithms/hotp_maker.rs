@@ -2,6 +2,7 @@ use std::convert::TryInto;
 
 use data_encoding::BASE32_NOPAD;
 use hmac::{EagerHash, Hmac, KeyInit, Mac};
+use md5::Md5;
 use sha1::Sha1;
 use sha2::{Sha256, Sha512};
 
@@ -10,9 +11,10 @@ use crate::otp::otp_error::OtpError;
 
 pub fn hotp(secret: &str, algorithm: OTPAlgorithm, counter: u64) -> Result<u32, OtpError> {
     match algorithm {
+        OTPAlgorithm::Sha1 => generate_hotp::<Sha1>(secret, counter),
         OTPAlgorithm::Sha256 => generate_hotp::<Sha256>(secret, counter),
         OTPAlgorithm::Sha512 => generate_hotp::<Sha512>(secret, counter),
-        _ => generate_hotp::<Sha1>(secret, counter),
+        OTPAlgorithm::Md5 => generate_hotp::<Md5>(secret, counter),
     }
 }
 
@@ -20,23 +22,34 @@ fn generate_hotp<D>(secret: &str, counter: u64) -> Result<u32, OtpError>
 where
     D: EagerHash,
 {
-    // decode the base32 secret
-    let secret_decoded = BASE32_NOPAD
-        .decode(secret.as_bytes())
-        .map_err(|e| OtpError::SecretEncoding(e.kind, e.position))?;
-
+    let secret_decoded = decode_secret(secret)?;
     let hash = hotp_hash::<D>(&secret_decoded, counter);
+    dynamic_truncation(&hash)
+}
+
+/// Decodes a base32 secret
+pub(crate) fn decode_secret(secret: &str) -> Result<Vec<u8>, OtpError> {
+    BASE32_NOPAD
+        .decode(secret.as_bytes())
+        .map_err(|e| OtpError::SecretEncoding(e.kind, e.position))
+}
 
+/// Extracts a 31 bits value from the given HMAC, as described in RFC 4226.
+/// MD5 digests are only 16 bytes long, so the offset is capped to the last 4 bytes of the digest,
+/// like the tokens supporting HMAC-MD5 do, instead of pointing past its end.
+pub(crate) fn dynamic_truncation(hash: &[u8]) -> Result<u32, OtpError> {
     // calculate offset
     let offset: usize = match hash.last() {
         Some(result) => *result & 0xf,
         None => return Err(OtpError::InvalidOffset),
     } as usize;
+    let max_offset = hash.len().checked_sub(4).ok_or(OtpError::InvalidDigest)?;
+    let offset = offset.min(max_offset);
 
     // calculate code
-    let code_bytes: [u8; 4] = match hash[offset..offset + 4].try_into() {
-        Ok(x) => x,
-        Err(_) => return Err(OtpError::InvalidDigest),
+    let code_bytes: [u8; 4] = match hash.get(offset..offset + 4).map(TryInto::try_into) {
+        Some(Ok(x)) => x,
+        _ => return Err(OtpError::InvalidDigest),
     };
     Ok(u32::from_be_bytes(code_bytes) & 0x7fffffff)
 }
@@ -46,17 +59,38 @@ where
     D: EagerHash,
 {
     // calculate HMAC from secret bytes and counter
-    let mut hmac: Hmac<D> = Hmac::new_from_slice(secret).expect("Failed to derive HMAC");
-    hmac.update(&counter.to_be_bytes());
+    hmac::<D>(secret, &counter.to_be_bytes())
+}
+
+/// Calculates the HMAC of the given message using the given algorithm
+pub(crate) fn hmac_digest(algorithm: OTPAlgorithm, key: &[u8], message: &[u8]) -> Vec<u8> {
+    match algorithm {
+        OTPAlgorithm::Sha1 => hmac::<Sha1>(key, message),
+        OTPAlgorithm::Sha256 => hmac::<Sha256>(key, message),
+        OTPAlgorithm::Sha512 => hmac::<Sha512>(key, message),
+        OTPAlgorithm::Md5 => hmac::<Md5>(key, message),
+    }
+}
+
+fn hmac<D>(key: &[u8], message: &[u8]) -> Vec<u8>
+where
+    D: EagerHash,
+{
+    let mut hmac: Hmac<D> = Hmac::new_from_slice(key).expect("Failed to derive HMAC");
+    hmac.update(message);
     hmac.finalize().into_bytes().to_vec()
 }
 
 #[cfg(test)]
 mod tests {
+    use md5::Md5;
     use sha1::Sha1;
 
     use crate::otp::algorithms::hotp_maker::generate_hotp;
 
+    // RFC 4226 test secret "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
     #[test]
     fn test_hotp() {
         assert_eq!(
@@ -72,4 +106,22 @@ mod tests {
             generate_hotp::<Sha1>("BASE32SECRET3232", 1).unwrap()
         );
     }
+
+    #[test]
+    fn test_hotp_md5() {
+        assert_eq!(1578532013, generate_hotp::<Md5>(RFC_SECRET, 1).unwrap());
+        assert_eq!(710848120, generate_hotp::<Md5>(RFC_SECRET, 3).unwrap());
+        assert_eq!(
+            2137920460,
+            generate_hotp::<Md5>("BASE32SECRET3232", 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hotp_md5_offset_past_digest_end() {
+        // The offsets 15, 14 and 13 are capped to 12
+        assert_eq!(901671151, generate_hotp::<Md5>(RFC_SECRET, 0).unwrap());
+        assert_eq!(1525154574, generate_hotp::<Md5>(RFC_SECRET, 2).unwrap());
+        assert_eq!(855208349, generate_hotp::<Md5>(RFC_SECRET, 4).unwrap());
+    }
 }
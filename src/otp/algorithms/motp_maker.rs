@@ -1,12 +1,6 @@
 use md5::{Digest, Md5};
-use std::time::SystemTime;
-
-pub fn motp(secret: &str, pin: &str, period: u64, digits: usize) -> String {
-    let seconds = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
 
+pub fn motp(secret: &str, pin: &str, period: u64, digits: usize, seconds: u64) -> String {
     get_motp_code(secret, pin, period, digits, seconds)
 }
 
@@ -0,0 +1,415 @@
+// Implements OCRA, the OATH challenge-response algorithm described in RFC 6287
+
+use std::str::FromStr;
+
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha512};
+
+use crate::otp::otp_algorithm::OTPAlgorithm;
+use crate::otp::otp_element::format_code;
+use crate::otp::otp_error::OtpError;
+
+use super::hotp_maker::{decode_secret, dynamic_truncation, hmac_digest};
+
+/// Size in bytes of the challenge in the hashed message
+const CHALLENGE_LENGTH: usize = 128;
+const DEFAULT_SESSION_LENGTH: usize = 64;
+/// Largest session information length defined by the RFC, S512
+const MAX_SESSION_LENGTH: usize = 512;
+
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ChallengeFormat {
+    Numeric,
+    Alphanumeric,
+    Hex,
+}
+
+/// Parsed OCRA suite, like OCRA-1:HOTP-SHA1-6:C-QN08-PSHA1
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct OcraSuite {
+    suite: String,
+    pub algorithm: OTPAlgorithm,
+    pub digits: u64,
+    /// The response depends on a counter
+    pub counter: bool,
+    pub challenge_format: ChallengeFormat,
+    pub challenge_length: usize,
+    /// Algorithm used to hash the PIN, if the response depends on it
+    pub pin_hash: Option<OTPAlgorithm>,
+    /// Length in bytes of the session information, if the response depends on it
+    pub session_length: Option<usize>,
+    /// Time step in seconds, if the response depends on the time
+    pub time_step: Option<u64>,
+}
+
+/// Values the response is computed from, only the ones required by the suite are used
+#[derive(Default)]
+pub struct OcraInput<'a> {
+    pub challenge: &'a str,
+    pub counter: Option<u64>,
+    pub pin: Option<&'a str>,
+    /// Session information, hex encoded
+    pub session: Option<&'a str>,
+    /// Unix timestamp, in seconds
+    pub time: u64,
+}
+
+impl FromStr for OcraSuite {
+    type Err = OtpError;
+
+    fn from_str(suite: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason: &str| OtpError::InvalidOcraSuite(format!("{suite}: {reason}"));
+
+        let [version, crypto_function, data_input] = suite
+            .split(':')
+            .collect::<Vec<&str>>()
+            .try_into()
+            .map_err(|_| invalid("expected three parts separated by colons"))?;
+        if !version.eq_ignore_ascii_case("OCRA-1") {
+            return Err(invalid("only OCRA-1 is supported"));
+        }
+
+        let (algorithm, digits) = match crypto_function
+            .to_uppercase()
+            .split('-')
+            .collect::<Vec<_>>()[..]
+        {
+            ["HOTP", algorithm, digits] => (
+                match algorithm {
+                    "SHA1" => OTPAlgorithm::Sha1,
+                    "SHA256" => OTPAlgorithm::Sha256,
+                    "SHA512" => OTPAlgorithm::Sha512,
+                    _ => return Err(invalid("unsupported hash function")),
+                },
+                digits
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|d| (4..=10).contains(d))
+                    .ok_or_else(|| invalid("digits must be between 4 and 10"))?,
+            ),
+            _ => return Err(invalid("expected a crypto function like HOTP-SHA1-6")),
+        };
+
+        let mut parsed = OcraSuite {
+            suite: suite.to_string(),
+            algorithm,
+            digits,
+            counter: false,
+            challenge_format: ChallengeFormat::Numeric,
+            challenge_length: 0,
+            pin_hash: None,
+            session_length: None,
+            time_step: None,
+        };
+        for input in data_input.to_uppercase().split('-') {
+            let (kind, value) = input.split_at(input.len().min(1));
+            match kind {
+                "C" if value.is_empty() => parsed.counter = true,
+                "Q" => {
+                    let (format, length) = value.split_at(value.len().min(1));
+                    parsed.challenge_format = match format {
+                        "N" => ChallengeFormat::Numeric,
+                        "A" => ChallengeFormat::Alphanumeric,
+                        "H" => ChallengeFormat::Hex,
+                        _ => return Err(invalid("unknown challenge format")),
+                    };
+                    parsed.challenge_length = length
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|l| (4..=64).contains(l))
+                        .ok_or_else(|| invalid("challenge length must be between 04 and 64"))?;
+                }
+                "P" => {
+                    parsed.pin_hash = Some(match value {
+                        "SHA1" => OTPAlgorithm::Sha1,
+                        "SHA256" => OTPAlgorithm::Sha256,
+                        "SHA512" => OTPAlgorithm::Sha512,
+                        _ => return Err(invalid("unsupported PIN hash function")),
+                    })
+                }
+                "S" => {
+                    parsed.session_length = Some(if value.is_empty() {
+                        DEFAULT_SESSION_LENGTH
+                    } else {
+                        value
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|l| (1..=MAX_SESSION_LENGTH).contains(l))
+                            .ok_or_else(|| {
+                                invalid("session information length must be between 001 and 512")
+                            })?
+                    })
+                }
+                "T" => {
+                    let (amount, unit) = value.split_at(value.len().saturating_sub(1));
+                    let (unit, max) = match unit {
+                        "S" => (1, 59),
+                        "M" => (60, 59),
+                        "H" => (3600, 48),
+                        _ => return Err(invalid("time step unit must be S, M or H")),
+                    };
+                    parsed.time_step = amount
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|a| (1..=max).contains(a))
+                        .map(|a| a * unit);
+                    if parsed.time_step.is_none() {
+                        return Err(invalid("time step must be 1-59S, 1-59M or 1-48H"));
+                    }
+                }
+                _ => return Err(invalid(&format!("unknown data input {input}"))),
+            }
+        }
+        if parsed.challenge_length == 0 {
+            return Err(invalid("missing challenge format, like QN08"));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Computes the response to the given challenge
+pub fn ocra(secret: &str, suite: &OcraSuite, input: &OcraInput) -> Result<String, OtpError> {
+    let key = decode_secret(secret)?;
+
+    // The message contains the suite, a zero byte separator and the data inputs
+    let mut message = suite.suite.as_bytes().to_vec();
+    message.push(0);
+    if suite.counter {
+        let counter = input.counter.ok_or(OtpError::MissingCounter)?;
+        message.extend_from_slice(&counter.to_be_bytes());
+    }
+    message.extend(encode_challenge(suite, input.challenge)?);
+    if let Some(algorithm) = suite.pin_hash {
+        let pin = input.pin.ok_or(OtpError::MissingPin)?;
+        message.extend(hash_pin(algorithm, pin));
+    }
+    if let Some(length) = suite.session_length {
+        let session = input.session.ok_or_else(|| {
+            OtpError::InvalidOcraInput("the suite requires session information".to_string())
+        })?;
+        message.extend(encode_session(session, length)?);
+    }
+    if let Some(time_step) = suite.time_step {
+        message.extend_from_slice(&(input.time / time_step).to_be_bytes());
+    }
+
+    let hash = hmac_digest(suite.algorithm, &key, &message);
+    format_code(suite.digits, dynamic_truncation(&hash)?)
+}
+
+/// Encodes the challenge as hex, padded with zeros on the right.
+/// The mutual mode concatenates the client and server challenges, exceeding the length of the suite,
+/// so only the size of the message field is enforced.
+fn encode_challenge(suite: &OcraSuite, challenge: &str) -> Result<Vec<u8>, OtpError> {
+    let challenge = challenge.trim();
+    let invalid = |reason: &str| OtpError::InvalidOcraInput(format!("challenge {reason}"));
+    if challenge.is_empty() {
+        return Err(invalid("must not be empty"));
+    }
+    let mut hex_challenge = match suite.challenge_format {
+        ChallengeFormat::Numeric if challenge.chars().all(|c| c.is_ascii_digit()) => {
+            decimal_to_hex(challenge)
+        }
+        ChallengeFormat::Alphanumeric if challenge.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            hex::encode(challenge)
+        }
+        ChallengeFormat::Hex if challenge.chars().all(|c| c.is_ascii_hexdigit()) => {
+            challenge.to_string()
+        }
+        format => return Err(invalid(&format!("is not in the {format:?} format"))),
+    };
+    if hex_challenge.len() > CHALLENGE_LENGTH * 2 {
+        return Err(invalid(&format!("longer than {CHALLENGE_LENGTH} bytes")));
+    }
+    hex_challenge.extend(std::iter::repeat_n(
+        '0',
+        (CHALLENGE_LENGTH * 2).saturating_sub(hex_challenge.len()),
+    ));
+    hex::decode(hex_challenge).map_err(|e| invalid(&e.to_string()))
+}
+
+/// Decodes the hex session information, padded with zeros on the left
+fn encode_session(session: &str, length: usize) -> Result<Vec<u8>, OtpError> {
+    let session = session.trim();
+    if session.len() > length * 2 {
+        return Err(OtpError::InvalidOcraInput(format!(
+            "session information longer than {length} bytes"
+        )));
+    }
+    hex::decode(format!("{session:0>width$}", width = length * 2))
+        .map_err(|e| OtpError::InvalidOcraInput(format!("session information: {e}")))
+}
+
+fn hash_pin(algorithm: OTPAlgorithm, pin: &str) -> Vec<u8> {
+    match algorithm {
+        OTPAlgorithm::Sha256 => Sha256::digest(pin).to_vec(),
+        OTPAlgorithm::Sha512 => Sha512::digest(pin).to_vec(),
+        OTPAlgorithm::Sha1 | OTPAlgorithm::Md5 => Sha1::digest(pin).to_vec(),
+    }
+}
+
+/// Converts a decimal number of any length into hex, by repeated divisions
+fn decimal_to_hex(decimal: &str) -> String {
+    let mut digits: Vec<u32> = decimal.bytes().map(|b| u32::from(b - b'0')).collect();
+    let mut hex = Vec::new();
+    while digits.iter().any(|d| *d != 0) {
+        let mut remainder = 0;
+        for digit in digits.iter_mut() {
+            let value = remainder * 10 + *digit;
+            *digit = value / 16;
+            remainder = value % 16;
+        }
+        hex.push(char::from_digit(remainder, 16).unwrap_or('0'));
+    }
+    if hex.is_empty() {
+        hex.push('0');
+    }
+    hex.iter().rev().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::{OcraInput, OcraSuite, decimal_to_hex, ocra};
+
+    // RFC 6287 test keys, "12345678901234567890" repeated to 20, 32 and 64 bytes
+    const SEED: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+    const SEED_32: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZA";
+    const SEED_64: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQGEZDGNA";
+    const PIN: &str = "1234";
+    // 2008-03-25 12:06:30 UTC, the 0x132d0b6 minutes step of the RFC
+    const TIME: u64 = 0x132d0b6 * 60;
+
+    fn responses(
+        secret: &str,
+        suite: &str,
+        inputs: impl Iterator<Item = (String, Option<u64>)>,
+    ) -> Vec<String> {
+        let suite = OcraSuite::from_str(suite).unwrap();
+        inputs
+            .map(|(challenge, counter)| {
+                let input = OcraInput {
+                    challenge: &challenge,
+                    counter,
+                    pin: Some(PIN),
+                    time: TIME,
+                    ..Default::default()
+                };
+                ocra(secret, &suite, &input).unwrap()
+            })
+            .collect()
+    }
+
+    fn repeated_digits(count: u8) -> impl Iterator<Item = (String, Option<u64>)> {
+        (0..count).map(|d| (d.to_string().repeat(8), None))
+    }
+
+    #[test]
+    fn test_one_way_challenge_response() {
+        assert_eq!(
+            vec![
+                "237653", "243178", "653583", "740991", "608993", "388898", "816933", "224598",
+                "750600", "294470"
+            ],
+            responses(SEED, "OCRA-1:HOTP-SHA1-6:QN08", repeated_digits(10))
+        );
+        assert_eq!(
+            vec![
+                "65347737", "86775851", "78192410", "71565254", "10104329", "65983500", "70069104",
+                "91771096", "75011558", "08522129"
+            ],
+            responses(
+                SEED_32,
+                "OCRA-1:HOTP-SHA256-8:C-QN08-PSHA1",
+                (0..10).map(|c| ("12345678".to_string(), Some(c)))
+            )
+        );
+        assert_eq!(
+            vec!["83238735", "01501458", "17957585", "86776967", "86807031"],
+            responses(
+                SEED_32,
+                "OCRA-1:HOTP-SHA256-8:QN08-PSHA1",
+                repeated_digits(5)
+            )
+        );
+        assert_eq!(
+            vec![
+                "07016083", "63947962", "70123924", "25341727", "33203315", "34205738", "44343969",
+                "51946085", "20403879", "31409299"
+            ],
+            responses(
+                SEED_64,
+                "OCRA-1:HOTP-SHA512-8:C-QN08",
+                (0..10).map(|c| (c.to_string().repeat(8), Some(c as u64)))
+            )
+        );
+        assert_eq!(
+            vec!["95209754", "55907591", "22048402", "24218844", "36209546"],
+            responses(SEED_64, "OCRA-1:HOTP-SHA512-8:QN08-T1M", repeated_digits(5))
+        );
+    }
+
+    #[test]
+    fn test_mutual_challenge_response() {
+        let challenges = |challenges: &[&str]| {
+            challenges
+                .iter()
+                .map(|c| (c.to_string(), None))
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        assert_eq!(
+            vec!["28247970", "01984843", "65387857", "03351211", "83412541"],
+            responses(
+                SEED_32,
+                "OCRA-1:HOTP-SHA256-8:QA08",
+                challenges(&[
+                    "CLI22220SRV11110",
+                    "CLI22221SRV11111",
+                    "CLI22222SRV11112",
+                    "CLI22223SRV11113",
+                    "CLI22224SRV11114"
+                ])
+            )
+        );
+        assert_eq!(
+            vec!["77537423"],
+            responses(
+                SEED_64,
+                "OCRA-1:HOTP-SHA512-8:QA10-T1M",
+                challenges(&["SIG1000000"])
+            )
+        );
+    }
+
+    #[test]
+    fn test_invalid_suites() {
+        assert!(OcraSuite::from_str("OCRA-2:HOTP-SHA1-6:QN08").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-MD5-6:QN08").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:C").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QX08").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QN08-T0M").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QN08-T60S").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QN08-T49H").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QN08-T99999999999999999999H").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QN08-S513").is_err());
+        assert!(OcraSuite::from_str("OCRA-1:HOTP-SHA1-6:QN08-S18446744073709551615").is_err());
+        assert_eq!(
+            Some(128),
+            OcraSuite::from_str("ocra-1:hotp-sha1-6:qa08-s128")
+                .unwrap()
+                .session_length
+        );
+    }
+
+    #[test]
+    fn test_decimal_to_hex() {
+        assert_eq!("0", decimal_to_hex("00000000"));
+        assert_eq!("BC614E", decimal_to_hex("12345678").to_uppercase());
+        assert_eq!(
+            "8AC7230489E80000",
+            decimal_to_hex("10000000000000000000").to_uppercase()
+        );
+    }
+}
@@ -0,0 +1,57 @@
+// Symantec VIP Access credentials, which generate standard TOTP codes identified by a credential ID
+
+use crate::otp::otp_algorithm::OTPAlgorithm;
+use crate::otp::otp_element::format_code;
+use crate::otp::otp_error::OtpError;
+
+use super::totp_maker::totp;
+
+pub const SYMANTEC_DIGITS: u64 = 6;
+pub const SYMANTEC_PERIOD: u64 = 30;
+
+pub fn symantec(secret: &str, time: u64) -> Result<String, OtpError> {
+    let code = totp(secret, OTPAlgorithm::Sha1, SYMANTEC_PERIOD, 0, time)?;
+    format_code(SYMANTEC_DIGITS, code)
+}
+
+/// Formats a credential ID like SYMC12345678, made of a four letters prefix and 8 digits
+pub fn normalize_credential_id(credential_id: &str) -> Result<String, OtpError> {
+    let compact: String = credential_id
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_uppercase();
+    let (prefix, digits) = compact.split_at(compact.len().min(4));
+    if prefix.len() == 4
+        && prefix.chars().all(|c| c.is_ascii_alphabetic())
+        && digits.len() == 8
+        && digits.chars().all(|c| c.is_ascii_digit())
+    {
+        Ok(compact)
+    } else {
+        Err(OtpError::InvalidCredentialId(credential_id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_credential_id, symantec};
+
+    #[test]
+    fn test_symantec_code() {
+        assert_eq!(
+            Ok("287082".to_string()),
+            symantec("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ", 59)
+        );
+    }
+
+    #[test]
+    fn test_credential_id_format() {
+        assert_eq!(
+            Ok("VSST12345678".to_string()),
+            normalize_credential_id("vsst-1234 5678")
+        );
+        assert!(normalize_credential_id("VSST1234567").is_err());
+        assert!(normalize_credential_id("VS1212345678").is_err());
+    }
+}
@@ -1,26 +1,29 @@
-use std::time::SystemTime;
-
 use crate::otp::otp_algorithm::OTPAlgorithm;
 use crate::otp::otp_error::OtpError;
 
 use super::hotp_maker::hotp;
 
-pub fn totp(secret: &str, algorithm: OTPAlgorithm, period: u64) -> Result<u32, OtpError> {
-    let time = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    generate_totp(secret, algorithm, time, period, 0)
+pub fn totp(
+    secret: &str,
+    algorithm: OTPAlgorithm,
+    period: u64,
+    epoch: u64,
+    time: u64,
+) -> Result<u32, OtpError> {
+    generate_totp(secret, algorithm, time, epoch, period, 0)
 }
 
-fn generate_totp(
+/// Generates the code of the time step containing the given time, counting the steps from the epoch (T0)
+pub(crate) fn generate_totp(
     secret: &str,
     algorithm: OTPAlgorithm,
     time: u64,
+    epoch: u64,
     time_step: u64,
     skew: i64,
 ) -> Result<u32, OtpError> {
-    hotp(secret, algorithm, ((time as i64 + skew) as u64) / time_step)
+    let time = ((time as i64 + skew) as u64).saturating_sub(epoch);
+    hotp(secret, algorithm, time / time_step)
 }
 
 #[cfg(test)]
@@ -34,7 +37,7 @@ mod tests {
     fn test_totp() {
         assert_eq!(
             455260182,
-            generate_totp("BASE32SECRET3232", OTPAlgorithm::Sha1, 0, 30, 0).unwrap()
+            generate_totp("BASE32SECRET3232", OTPAlgorithm::Sha1, 0, 0, 30, 0).unwrap()
         );
     }
 
@@ -45,6 +48,7 @@ mod tests {
             "DEADBEEFDEADBEEFDEADBEEFDEADBEEF",
             OTPAlgorithm::Sha1,
             1777799540,
+            0,
             60,
             0,
         )
@@ -55,4 +59,36 @@ mod tests {
         // Assert
         assert_eq!("295439", code)
     }
+
+    #[test]
+    fn test_totp_md5() {
+        assert_eq!(
+            713672061,
+            generate_totp(
+                "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ",
+                OTPAlgorithm::Md5,
+                1111111109,
+                0,
+                30,
+                0
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_totp_with_custom_epoch() {
+        // Arrange / Act
+        let shifted = generate_totp(
+            "BASE32SECRET3232",
+            OTPAlgorithm::Sha1,
+            1_000_000_000,
+            1_000_000_000,
+            30,
+            0,
+        );
+
+        // Assert
+        assert_eq!(Ok(455260182), shifted);
+    }
 }
@@ -1,7 +1,5 @@
 // Ported from https://github.com/beemdevelopment/Aegis/blob/3d13117752491de81d3779dae34407c651954f7b/app/src/main/java/com/beemdevelopment/aegis/crypto/otp/YAOTP.java
 
-use std::time::SystemTime;
-
 use data_encoding::BASE32_NOPAD;
 use hmac::EagerHash;
 use sha1::{Digest, Sha1};
@@ -9,6 +7,7 @@ use sha2::{Sha256, Sha512};
 
 use crate::otp::otp_algorithm::OTPAlgorithm;
 use crate::otp::otp_error::OtpError;
+use crate::otp::otp_type::OTPType;
 
 use super::hotp_maker::hotp_hash;
 
@@ -21,11 +20,8 @@ pub fn yandex(
     period: u64,
     digits: usize,
     algorithm: OTPAlgorithm,
+    seconds: u64,
 ) -> Result<String, OtpError> {
-    let seconds = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
     match algorithm {
         OTPAlgorithm::Sha256 => {
             calculate_yandex_code::<Sha256>(secret, pin, period, digits, seconds)
@@ -35,7 +31,9 @@ pub fn yandex(
             calculate_yandex_code::<Sha512>(secret, pin, period, digits, seconds)
         }
 
-        _ => calculate_yandex_code::<Sha1>(secret, pin, period, digits, seconds),
+        OTPAlgorithm::Sha1 => calculate_yandex_code::<Sha1>(secret, pin, period, digits, seconds),
+
+        OTPAlgorithm::Md5 => Err(OtpError::UnsupportedAlgorithm(OTPType::Yandex, algorithm)),
     }
 }
 
@@ -112,7 +110,9 @@ fn to_yandex_string(mut code: u64, digits: usize) -> String {
 mod tests {
     use sha2::Sha256;
 
-    use super::calculate_yandex_code;
+    use crate::otp::{otp_algorithm::OTPAlgorithm, otp_error::OtpError, otp_type::OTPType};
+
+    use super::{calculate_yandex_code, yandex};
 
     #[test]
     fn test_yandex() {
@@ -130,4 +130,22 @@ mod tests {
             "umozdicq".to_uppercase()
         );
     }
+
+    #[test]
+    fn test_yandex_md5_is_unsupported() {
+        assert_eq!(
+            Err(OtpError::UnsupportedAlgorithm(
+                OTPType::Yandex,
+                OTPAlgorithm::Md5
+            )),
+            yandex(
+                "6SB2IKNM6OBZPAVBVTOHDKS4FAAAAAAADFUTQMBTRY",
+                "5239",
+                30,
+                8,
+                OTPAlgorithm::Md5,
+                0
+            )
+        );
+    }
 }
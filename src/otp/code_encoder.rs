@@ -0,0 +1,141 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::{otp_element::format_code, otp_error::OtpError};
+
+const STEAM_ALPHABET: &str = "23456789BCDFGHJKMNPQRTVWXY";
+const ALPHABET_PREFIX: &str = "alphabet:";
+
+/// How the generated value is rendered into the displayed code
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeEncoder {
+    /// Decimal digits, as defined by the HOTP and TOTP standards
+    #[default]
+    Decimal,
+    /// Alphanumeric codes of the Steam mobile authenticator
+    Steam,
+    /// Characters of the given alphabet, the first one being the zero, most significant first
+    Alphabet(String),
+}
+
+impl CodeEncoder {
+    /// Checks if the codes are rendered as plain decimal digits
+    pub fn is_decimal(&self) -> bool {
+        *self == CodeEncoder::Decimal
+    }
+
+    /// Renders the value using the given number of digits or characters
+    pub fn encode(&self, value: u32, digits: u64) -> Result<String, OtpError> {
+        match self {
+            CodeEncoder::Decimal => format_code(digits, value),
+            CodeEncoder::Steam => Ok(encode_with_alphabet(value, digits, STEAM_ALPHABET)),
+            CodeEncoder::Alphabet(alphabet) => {
+                validate_alphabet(alphabet)?;
+                Ok(encode_with_alphabet(value, digits, alphabet)
+                    .chars()
+                    .rev()
+                    .collect())
+            }
+        }
+    }
+}
+
+impl fmt::Display for CodeEncoder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodeEncoder::Decimal => f.write_str("decimal"),
+            CodeEncoder::Steam => f.write_str("steam"),
+            CodeEncoder::Alphabet(alphabet) => write!(f, "{ALPHABET_PREFIX}{alphabet}"),
+        }
+    }
+}
+
+impl FromStr for CodeEncoder {
+    type Err = OtpError;
+
+    /// Parses decimal, steam or alphabet:CHARACTERS
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            s if s.eq_ignore_ascii_case("decimal") => Ok(CodeEncoder::Decimal),
+            s if s.eq_ignore_ascii_case("steam") => Ok(CodeEncoder::Steam),
+            s => {
+                let alphabet = s
+                    .strip_prefix(ALPHABET_PREFIX)
+                    .ok_or(OtpError::InvalidAlphabet)?;
+                validate_alphabet(alphabet)?;
+                Ok(CodeEncoder::Alphabet(alphabet.to_string()))
+            }
+        }
+    }
+}
+
+impl Zeroize for CodeEncoder {
+    fn zeroize(&mut self) {
+        *self = CodeEncoder::Decimal;
+    }
+}
+
+/// Alphabets must have at least two characters, all different
+fn validate_alphabet(alphabet: &str) -> Result<(), OtpError> {
+    let characters: Vec<char> = alphabet.chars().collect();
+    let unique = characters
+        .iter()
+        .enumerate()
+        .all(|(i, c)| !characters[..i].contains(c));
+    if characters.len() < 2 || !unique {
+        return Err(OtpError::InvalidAlphabet);
+    }
+    Ok(())
+}
+
+/// Writes the value in the base of the alphabet, least significant character first like Steam does
+fn encode_with_alphabet(mut value: u32, digits: u64, alphabet: &str) -> String {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    let base = alphabet.len() as u32;
+    (0..digits)
+        .map(|_| {
+            let c = alphabet[(value % base) as usize];
+            value /= base;
+            c
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::CodeEncoder;
+
+    #[test]
+    fn test_steam_code() {
+        assert_eq!(
+            Ok("GJ2F4".to_string()),
+            CodeEncoder::Steam.encode(36751792, 5)
+        );
+    }
+
+    #[test]
+    fn test_custom_alphabet() {
+        // Arrange
+        let encoder = CodeEncoder::from_str("alphabet:ABCDEFGHIJKLMNOPQRSTUVWXYZ").unwrap();
+
+        // Act
+        let code = encoder.encode(28, 3);
+
+        // Assert
+        assert_eq!(Ok("ABC".to_string()), code);
+        assert_eq!("alphabet:ABCDEFGHIJKLMNOPQRSTUVWXYZ", encoder.to_string());
+    }
+
+    #[test]
+    fn test_invalid_encoders() {
+        assert!(CodeEncoder::from_str("base64").is_err());
+        assert!(CodeEncoder::from_str("alphabet:A").is_err());
+        assert!(CodeEncoder::from_str("alphabet:ABA").is_err());
+        assert_eq!(Ok(CodeEncoder::Steam), CodeEncoder::from_str("Steam"));
+    }
+}
@@ -1,7 +1,13 @@
 use color_eyre::eyre::ErrReport;
 use url::Url;
+use uuid::Uuid;
 
-use super::{otp_algorithm::OTPAlgorithm, otp_element::OTPElement, otp_type::OTPType};
+use super::{
+    code_encoder::CodeEncoder,
+    otp_algorithm::OTPAlgorithm,
+    otp_element::{CustomFields, OTPElement},
+    otp_type::OTPType,
+};
 
 pub trait FromOtpUri: Sized {
     fn from_otp_uri(otp_uri: &str) -> color_eyre::Result<Self>;
@@ -44,16 +50,47 @@ impl FromOtpUri for OTPElement {
             .find(|(k, _v)| k == "counter")
             .and_then(|(_k, v)| v.parse::<u64>().ok());
 
+        let epoch = parsed_uri
+            .query_pairs()
+            .find(|(k, _v)| k == "t0" || k == "epoch")
+            .and_then(|(_k, v)| v.parse::<u64>().ok())
+            .filter(|e| *e > 0);
+
+        let ocra_suite = parsed_uri
+            .query_pairs()
+            .find(|(k, _v)| k == "ocra_suite")
+            .map(|(_k, v)| v.to_string());
+        let type_ = OTPType::from(otp_type.as_str());
+        if type_ == OTPType::Ocra && ocra_suite.is_none() {
+            return Err(ErrReport::msg("OCRA suite not found in OTP Uri"));
+        }
+
         Ok(OTPElement {
             secret,
             issuer,
             label,
             digits,
-            type_: OTPType::from(otp_type.as_str()),
+            type_,
             algorithm: OTPAlgorithm::from(algorithm.as_str()),
             period,
             counter,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch,
+            ocra_suite,
+            encoder: CodeEncoder::Decimal,
         })
     }
 }
@@ -0,0 +1,200 @@
+use std::fmt::{self, Display};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::utils::current_timestamp;
+
+use super::otp_element::{OTPDatabase, OTPElement};
+
+/// Maximum number of changes kept in the database, the oldest ones are discarded first
+pub const MAX_HISTORY_LENGTH: usize = 100;
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryAction {
+    Add,
+    Edit,
+    CounterChange,
+    Archive,
+    Restore,
+}
+
+impl Display for HistoryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let action = match self {
+            Self::Add => "Added",
+            Self::Edit => "Edited",
+            Self::CounterChange => "Changed counter of",
+            Self::Archive => "Moved to the trash",
+            Self::Restore => "Restored",
+        };
+        f.write_str(action)
+    }
+}
+
+/// Change made to a code, storing what is needed to revert it
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Hash)]
+pub struct HistoryEntry {
+    pub timestamp: u64,
+    pub action: HistoryAction,
+    /// Id of the changed code
+    pub id: Uuid,
+    pub issuer: String,
+    pub label: String,
+    /// Copy of the code before the change, missing for the added ones
+    pub before: Option<OTPElement>,
+}
+
+impl Display for HistoryEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ({})", self.action, self.issuer, self.label)
+    }
+}
+
+impl OTPDatabase {
+    /// Records a change of the code at the given position. Must be called before changing it,
+    /// except for the added codes.
+    pub(crate) fn record_change(&mut self, index: usize, action: HistoryAction) {
+        let Some(element) = self.elements.get(index) else {
+            return;
+        };
+        let before = (action != HistoryAction::Add).then(|| element.clone());
+        self.history.push(HistoryEntry {
+            timestamp: current_timestamp(),
+            action,
+            id: element.id,
+            issuer: element.issuer.clone(),
+            label: element.label.clone(),
+            before,
+        });
+        if self.history.len() > MAX_HISTORY_LENGTH {
+            self.history
+                .drain(..self.history.len() - MAX_HISTORY_LENGTH);
+        }
+    }
+
+    /// Returns the recorded changes, from the oldest to the most recent
+    pub(crate) fn history(&self) -> &[HistoryEntry] {
+        &self.history
+    }
+
+    /// Reverts the most recent change and returns it
+    pub(crate) fn undo(&mut self) -> Result<HistoryEntry, String> {
+        let entry = self.history.pop().ok_or("There are no changes to undo")?;
+        let position = self.find_index_by_id(&entry.id);
+        match (entry.action, position, &entry.before) {
+            (HistoryAction::Add, Some(index), _) => {
+                self.elements.remove(index);
+            }
+            (
+                HistoryAction::Edit
+                | HistoryAction::CounterChange
+                | HistoryAction::Archive
+                | HistoryAction::Restore,
+                Some(index),
+                Some(before),
+            ) => {
+                self.elements[index] = before.clone();
+            }
+            _ => {
+                let message = format!("Cannot undo \"{entry}\", the code has changed since");
+                // Keep the entry, so the history is left untouched by the failed undo
+                self.history.push(entry);
+                return Err(message);
+            }
+        }
+        self.mark_modified();
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::otp_element::{OTPDatabase, OTPElementBuilder};
+
+    use super::{HistoryAction, MAX_HISTORY_LENGTH};
+
+    #[test]
+    fn test_undo_edit_and_add() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("first")
+                .build()
+                .unwrap(),
+        );
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("second")
+                .build()
+                .unwrap(),
+        );
+        database.record_change(1, HistoryAction::Edit);
+        database.mut_element(1).unwrap().issuer = "edited".to_string();
+
+        // Act
+        database.undo().unwrap();
+        database.undo().unwrap();
+
+        // Assert
+        assert_eq!(1, database.elements_ref().len());
+        assert_eq!("first", database.elements_ref()[0].issuer);
+        assert_eq!(1, database.history().len());
+    }
+
+    #[test]
+    fn test_failed_undo_keeps_the_entry() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("first")
+                .build()
+                .unwrap(),
+        );
+        database.record_change(0, HistoryAction::Edit);
+        database.elements.remove(0);
+
+        // Act
+        let undone = database.undo();
+
+        // Assert
+        assert!(undone.is_err());
+        assert_eq!(2, database.history().len());
+        assert_eq!(HistoryAction::Edit, database.history()[1].action);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("first")
+                .build()
+                .unwrap(),
+        );
+
+        // Act
+        (0..MAX_HISTORY_LENGTH).for_each(|_| database.record_change(0, HistoryAction::Edit));
+
+        // Assert
+        assert_eq!(MAX_HISTORY_LENGTH, database.history().len());
+        assert!(
+            database
+                .history()
+                .iter()
+                .all(|e| e.action == HistoryAction::Edit)
+        );
+    }
+}
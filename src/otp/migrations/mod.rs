@@ -1,18 +1,77 @@
-use super::otp_element::OTPDatabase;
+use uuid::Uuid;
+
+use super::otp_element::{ENCRYPTED_RECIPIENTS_VERSION, OTPDatabase};
 struct Migration<'a> {
     to_version: u16, // Database version which we are migrating on
     migration_function: &'a dyn Fn(&mut OTPDatabase) -> color_eyre::Result<()>, // Function to execute the migration
 }
-const MIGRATIONS_LIST: [Migration; 1] = [Migration {
-    to_version: 2,
-    migration_function: &migrate_to_2,
-}];
+const MIGRATIONS_LIST: [Migration; 5] = [
+    Migration {
+        to_version: 2,
+        migration_function: &migrate_to_2,
+    },
+    Migration {
+        to_version: 3,
+        migration_function: &migrate_to_3,
+    },
+    Migration {
+        to_version: 4,
+        migration_function: &migrate_to_4,
+    },
+    Migration {
+        to_version: 5,
+        migration_function: &migrate_to_5,
+    },
+    Migration {
+        to_version: ENCRYPTED_RECIPIENTS_VERSION,
+        migration_function: &migrate_to_6,
+    },
+];
 
 fn migrate_to_2(database: &mut OTPDatabase) -> color_eyre::Result<()> {
     database.version = 2;
     Ok(())
 }
 
+/// Gives every code a stable id, so it can be addressed regardless of its position
+fn migrate_to_3(database: &mut OTPDatabase) -> color_eyre::Result<()> {
+    database
+        .elements
+        .iter_mut()
+        .filter(|e| e.id.is_nil())
+        .for_each(|e| e.id = Uuid::new_v4());
+    database.version = 3;
+    Ok(())
+}
+
+/// Introduces the code tags, trimming and deduplicating the existing ones
+fn migrate_to_4(database: &mut OTPDatabase) -> color_eyre::Result<()> {
+    database.elements.iter_mut().for_each(|e| {
+        let tags = std::mem::take(&mut e.tags);
+        e.add_tags(&tags);
+    });
+    database.version = 4;
+    Ok(())
+}
+
+/// Introduces the user defined order, starting from the issuer order previously forced on load
+fn migrate_to_5(database: &mut OTPDatabase) -> color_eyre::Result<()> {
+    database.sort();
+    database
+        .elements
+        .iter_mut()
+        .zip(0..)
+        .for_each(|(e, position)| e.position = position);
+    database.version = 5;
+    Ok(())
+}
+
+/// Stores the age recipients in the encrypted database instead of the unauthenticated header
+fn migrate_to_6(database: &mut OTPDatabase) -> color_eyre::Result<()> {
+    database.version = ENCRYPTED_RECIPIENTS_VERSION;
+    Ok(())
+}
+
 pub fn migrate(database: &mut OTPDatabase) -> color_eyre::Result<()> {
     let mut binding = MIGRATIONS_LIST;
     let migrations = binding.as_mut();
@@ -25,3 +84,56 @@ pub fn migrate(database: &mut OTPDatabase) -> color_eyre::Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::otp_element::{CURRENT_DATABASE_VERSION, OTPDatabase, OTPElementBuilder};
+
+    use super::migrate;
+
+    #[test]
+    fn test_migration_assigns_unique_ids() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .issuer("issuer")
+            .label("label")
+            .secret("AA")
+            .build()
+            .unwrap();
+        let mut database: OTPDatabase = vec![element.clone(), element].into();
+
+        // Act
+        migrate(&mut database).unwrap();
+
+        // Assert
+        let elements = database.elements_ref();
+        assert_eq!(CURRENT_DATABASE_VERSION, database.version);
+        assert!(elements.iter().all(|e| !e.id.is_nil()));
+        assert_ne!(elements[0].id, elements[1].id);
+    }
+
+    #[test]
+    fn test_migration_keeps_issuer_order() {
+        // Arrange
+        let elements = ["b", "C", "a"].map(|issuer| {
+            OTPElementBuilder::default()
+                .issuer(issuer)
+                .label("label")
+                .secret("AA")
+                .build()
+                .unwrap()
+        });
+        let mut database: OTPDatabase = elements.to_vec().into();
+
+        // Act
+        migrate(&mut database).unwrap();
+
+        // Assert
+        let order: Vec<(&str, u64)> = database
+            .elements_ref()
+            .iter()
+            .map(|e| (e.issuer.as_str(), e.position))
+            .collect();
+        assert_eq!(vec![("a", 0), ("b", 1), ("C", 2)], order);
+    }
+}
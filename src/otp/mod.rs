@@ -1,7 +1,14 @@
 pub mod algorithms;
+pub mod code_encoder;
 pub mod from_otp_uri;
+pub mod history;
 pub mod migrations;
 pub mod otp_algorithm;
+pub mod otp_check;
 pub mod otp_element;
-mod otp_error;
+pub mod otp_error;
 pub mod otp_type;
+pub mod otp_verify;
+pub mod recovery_code;
+pub mod sort_order;
+pub mod trash;
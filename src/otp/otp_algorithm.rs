@@ -4,13 +4,18 @@ use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+/// Hash function used to generate the codes
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, ValueEnum, Hash, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OTPAlgorithm {
+    /// SHA-1, the default of most services
     #[default]
     Sha1,
+    /// SHA-256
     Sha256,
+    /// SHA-512
     Sha512,
+    /// MD5, only used by mOTP codes
     Md5,
 }
 
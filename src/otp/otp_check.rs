@@ -0,0 +1,173 @@
+use super::{
+    otp_element::{ALLOWED_DIGITS_RANGE, OTPElement, validate_secret},
+    otp_type::OTPType,
+};
+
+const DEFAULT_DIGITS: u64 = 6;
+const DEFAULT_STEAM_DIGITS: u64 = 5;
+const DEFAULT_PERIOD: u64 = 30;
+const DEFAULT_MOTP_PERIOD: u64 = 10;
+
+impl OTPElement {
+    /// Returns the problems which prevent this element from generating valid codes
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = vec![];
+
+        if self.secret.is_empty() {
+            problems.push("Secret must not be empty".to_string());
+        } else if let Err(e) = validate_secret(&self.secret, self.type_) {
+            problems.push(e.to_string());
+        }
+        if !ALLOWED_DIGITS_RANGE.contains(&self.digits) {
+            problems.push(format!(
+                "Digits value {} is outside the allowed range {}..={}",
+                self.digits,
+                ALLOWED_DIGITS_RANGE.start(),
+                ALLOWED_DIGITS_RANGE.end()
+            ));
+        }
+        if self.type_ == OTPType::Hotp && self.counter.is_none() {
+            problems.push("HOTP code without counter".to_string());
+        }
+        if matches!(self.type_, OTPType::Motp | OTPType::Yandex) && self.pin.is_none() {
+            problems.push(format!("{} code without pin", self.type_));
+        }
+        if self.is_time_based() && self.period == 0 {
+            problems.push("Period must be greater than zero".to_string());
+        }
+
+        if self.type_ == OTPType::Ocra {
+            // OCRA responses need a challenge, so only their suite can be checked
+            if let Err(e) = self.parsed_ocra_suite() {
+                problems.push(e.to_string());
+            }
+        } else if problems.is_empty()
+            && let Err(e) = self.get_otp_code()
+        {
+            // Only try to generate the code if it cannot fail for an already reported reason
+            problems.push(format!("Cannot generate the code: {e}"));
+        }
+        problems
+    }
+
+    /// Repairs the problems which can be fixed without changing the generated codes,
+    /// or filling the missing values with the default ones. Returns true if the element was modified.
+    pub fn fix(&mut self) -> bool {
+        let original = self.clone();
+
+        let secret: String = self
+            .secret
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-' && *c != '=')
+            .collect();
+        self.secret = if self.type_ == OTPType::Motp {
+            secret.to_lowercase()
+        } else {
+            secret.to_uppercase()
+        };
+
+        if !ALLOWED_DIGITS_RANGE.contains(&self.digits) {
+            self.digits = if self.type_ == OTPType::Steam {
+                DEFAULT_STEAM_DIGITS
+            } else {
+                DEFAULT_DIGITS
+            };
+        }
+        if self.type_ == OTPType::Hotp && self.counter.is_none() {
+            self.counter = Some(0);
+        }
+        if self.is_time_based() && self.period == 0 {
+            self.period = if self.type_ == OTPType::Motp {
+                DEFAULT_MOTP_PERIOD
+            } else {
+                DEFAULT_PERIOD
+            };
+        }
+
+        *self != original
+    }
+
+    fn is_time_based(&self) -> bool {
+        matches!(self.type_, OTPType::Totp | OTPType::Yandex | OTPType::Motp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::{otp_element::OTPElementBuilder, otp_type::OTPType};
+
+    #[test]
+    fn test_valid_element_has_no_problems() {
+        let element = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("issuer")
+            .build()
+            .unwrap();
+
+        assert!(element.check().is_empty());
+    }
+
+    #[test]
+    fn test_check_reports_all_problems() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("issuer")
+            .type_(OTPType::Hotp)
+            .build()
+            .unwrap();
+        element.digits = 11;
+
+        // Act
+        let problems = element.check();
+
+        // Assert
+        assert_eq!(2, problems.len());
+    }
+
+    #[test]
+    fn test_fix_normalizes_secret_and_defaults() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("JBSWY3DPEHPK3PXP")
+            .label("label")
+            .issuer("issuer")
+            .type_(OTPType::Hotp)
+            .build()
+            .unwrap();
+        element.secret = "jbsw y3dp-ehpk 3pxp====".to_string();
+        element.digits = 0;
+
+        // Act
+        let modified = element.fix();
+
+        // Assert
+        assert!(modified);
+        assert_eq!("JBSWY3DPEHPK3PXP", element.secret);
+        assert_eq!(6, element.digits);
+        assert_eq!(Some(0), element.counter);
+        assert!(element.check().is_empty());
+    }
+
+    #[test]
+    fn test_fix_cannot_guess_missing_pin() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("AAAF")
+            .label("label")
+            .issuer("issuer")
+            .type_(OTPType::Motp)
+            .build()
+            .unwrap();
+        element.secret = "AAAF".to_string();
+
+        // Act
+        element.fix();
+
+        // Assert
+        assert_eq!("aaaf", element.secret);
+        assert_eq!(vec!["MOTP code without pin"], element.check());
+    }
+}
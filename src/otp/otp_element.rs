@@ -1,34 +1,73 @@
 use color_eyre::eyre::{ErrReport, eyre};
 use derive_builder::Builder;
-use std::{fs::File, io::Write, vec};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::Write,
+    ops::{Deref, DerefMut},
+    path::Path,
+    vec,
+};
 
-use crate::crypto::cryptography::{argon_derive_key, encrypt_string_with_key, gen_salt};
+use crate::crypto::cryptography::{
+    add_recipients_key, argon_derive_key, encrypt_string_with_key, gen_salt,
+};
+use crate::crypto::encrypted_database::KdfParams;
 use crate::otp::otp_error::OtpError;
-use crate::path::DATABASE_PATH;
+use crate::path::database_path;
+use crate::utils::current_timestamp;
 use data_encoding::BASE32_NOPAD;
 use qrcode::QrCode;
 use qrcode::render::unicode;
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use super::{
     algorithms::{
-        hotp_maker::hotp, motp_maker::motp, steam_otp_maker::steam, totp_maker::totp,
+        blizzard_otp_maker::{BLIZZARD_DIGITS, BLIZZARD_PERIOD, blizzard, restore_code},
+        hotp_maker::hotp,
+        motp_maker::motp,
+        ocra_maker::{OcraInput, OcraSuite, ocra},
+        steam_otp_maker::{STEAM_OTP_PERIOD, steam},
+        symantec_otp_maker::{SYMANTEC_DIGITS, SYMANTEC_PERIOD, symantec},
+        totp_maker::totp,
         yandex_otp_maker::yandex,
     },
+    code_encoder::CodeEncoder,
+    history::{HistoryAction, HistoryEntry},
     migrations::migrate,
     otp_algorithm::OTPAlgorithm,
     otp_type::OTPType,
+    recovery_code::RecoveryCode,
 };
 
-pub const CURRENT_DATABASE_VERSION: u16 = 2;
+pub const CURRENT_DATABASE_VERSION: u16 = 6;
+/// Database version which moved the age recipients from the header to the encrypted database
+pub const ENCRYPTED_RECIPIENTS_VERSION: u16 = 6;
 
+/// Decrypted content of a cotp database
 #[derive(Serialize, Deserialize, PartialEq, Hash)]
 pub struct OTPDatabase {
     pub(crate) version: u16,
     pub(crate) elements: Vec<OTPElement>,
+    /// Journal of the most recent changes, used to undo them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) history: Vec<HistoryEntry>,
+    /// Days after which the codes in the trash are permanently deleted, never if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) trash_retention_days: Option<u64>,
     #[serde(skip)]
     pub(crate) needs_modification: bool,
+    /// Key derivation parameters read from the encrypted database header
+    #[serde(skip)]
+    pub(crate) kdf: KdfParams,
+    /// age recipients which can unlock the database, encrypted so they cannot be tampered with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) recipients: Vec<String>,
+    /// Set when only bookkeeping data changed, like the usage statistics, so the database can be saved silently
+    #[serde(skip)]
+    pub(crate) silently_modified: bool,
 }
 
 impl From<Vec<OTPElement>> for OTPDatabase {
@@ -37,7 +76,12 @@ impl From<Vec<OTPElement>> for OTPDatabase {
         OTPDatabase {
             version: 1,
             elements: val,
+            history: vec![],
+            trash_retention_days: None,
             needs_modification: true,
+            kdf: KdfParams::default(),
+            recipients: vec![],
+            silently_modified: false,
         }
     }
 }
@@ -47,83 +91,245 @@ impl Default for OTPDatabase {
         Self {
             version: CURRENT_DATABASE_VERSION,
             elements: vec![],
+            history: vec![],
+            trash_retention_days: None,
             needs_modification: false,
+            kdf: KdfParams::default(),
+            recipients: vec![],
+            silently_modified: false,
         }
     }
 }
 
 impl OTPDatabase {
+    /// Checks if the database has been changed since it was opened
     pub fn is_modified(&self) -> bool {
         self.needs_modification
     }
 
-    pub fn save(&mut self, key: &Vec<u8>, salt: &[u8]) -> color_eyre::Result<()> {
+    /// Checks if the database must be saved, even if only bookkeeping data changed
+    pub(crate) fn needs_saving(&self) -> bool {
+        self.needs_modification || self.silently_modified
+    }
+
+    /// Encrypts the database with the given key and writes it to the database path in use
+    pub(crate) fn save(&mut self, key: &Vec<u8>, salt: &[u8]) -> color_eyre::Result<()> {
+        self.save_to(&database_path(), key, salt)
+    }
+
+    /// Encrypts the database with the key and salt returned when it was opened, and writes it to the given path
+    pub fn save_to(&mut self, path: &Path, key: &Vec<u8>, salt: &[u8]) -> color_eyre::Result<()> {
         self.needs_modification = false;
+        self.silently_modified = false;
         migrate(self)?;
-        match self.overwrite_database_key(key, salt) {
-            Ok(()) => Ok(()),
-            Err(e) => Err(ErrReport::from(e)),
-        }
+        self.overwrite_database_key(path, key, salt)
     }
 
-    fn overwrite_database_key(&self, key: &Vec<u8>, salt: &[u8]) -> Result<(), std::io::Error> {
+    fn overwrite_database_key(
+        &self,
+        path: &Path,
+        key: &Vec<u8>,
+        salt: &[u8],
+    ) -> color_eyre::Result<()> {
         let json: &str = &serde_json::to_string(&self)?;
-        let encrypted = encrypt_string_with_key(json, key, salt).unwrap();
-        let mut file = File::create(DATABASE_PATH.get().unwrap())?;
-        match serde_json::to_string(&encrypted) {
-            Ok(content) => {
-                file.write_all(content.as_bytes())?;
-                file.sync_all()?;
-                Ok(())
-            }
-            Err(e) => Err(std::io::Error::from(e)),
-        }
+        let mut encrypted = encrypt_string_with_key(json, key, salt, self.kdf)?;
+        add_recipients_key(&mut encrypted, key, &self.recipients)?;
+        let content = serde_json::to_string(&encrypted)?;
+        let mut file = File::create(path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        Ok(())
     }
 
-    pub fn save_with_pw(&mut self, password: &str) -> color_eyre::Result<(Vec<u8>, [u8; 16])> {
+    /// Encrypts the database with a key derived from the given password and a new salt, using its
+    /// key derivation parameters, and writes it to the given path. Also used to change the password.
+    /// Returns the key and salt required by [`OTPDatabase::save_to`].
+    ///
+    /// ```
+    /// use cotp::{OTPDatabase, open_database};
+    ///
+    /// let path = std::env::temp_dir().join("cotp_save_with_password.cotp");
+    /// let mut database = OTPDatabase::default();
+    /// database.save_with_password(&path, "old password")?;
+    ///
+    /// let (mut database, _, _) = open_database(&path, "old password")?;
+    /// database.save_with_password(&path, "new password")?;
+    /// assert!(open_database(&path, "old password").is_err());
+    /// assert!(open_database(&path, "new password").is_ok());
+    /// # std::fs::remove_file(path)?;
+    /// # Ok::<(), color_eyre::Report>(())
+    /// ```
+    pub fn save_with_password(
+        &mut self,
+        path: &Path,
+        password: &str,
+    ) -> color_eyre::Result<(Vec<u8>, [u8; 16])> {
         let salt = gen_salt()?;
-        let key = argon_derive_key(password.as_bytes(), &salt)?;
-        self.save(&key, &salt)?;
+        let key = argon_derive_key(password.as_bytes(), &salt, &self.kdf)?;
+        self.save_to(path, &key, &salt)?;
         Ok((key, salt))
     }
 
-    pub fn add_all(&mut self, mut elements: Vec<OTPElement>) {
-        self.mark_modified();
-        self.elements.append(&mut elements);
+    /// Adds all the given codes, like [`OTPDatabase::add_element`]
+    pub fn add_all(&mut self, elements: Vec<OTPElement>) {
+        elements
+            .into_iter()
+            .for_each(|element| self.add_element(element));
     }
 
-    pub fn add_element(&mut self, element: OTPElement) {
+    /// Adds a code at the end of the user defined order, giving it a new id if missing or already in use
+    pub fn add_element(&mut self, mut element: OTPElement) {
         self.mark_modified();
+        // Imported codes may come without an id, or with one which is already in use
+        if element.id.is_nil() || self.find_index_by_id(&element.id).is_some() {
+            element.id = Uuid::new_v4();
+        }
+        if element.created_at.is_none() {
+            element.created_at = Some(current_timestamp());
+        }
+        // New codes are shown after the existing ones
+        element.position = self
+            .elements
+            .iter()
+            .map(|e| e.position + 1)
+            .max()
+            .unwrap_or_default();
         self.elements.push(element);
+        self.record_change(self.elements.len() - 1, HistoryAction::Add);
     }
 
+    /// Returns the position of the code with the given id
+    pub fn find_index_by_id(&self, id: &Uuid) -> Option<usize> {
+        self.elements.iter().position(|e| &e.id == id)
+    }
+
+    /// Marks the database as changed, needed after editing the codes returned by
+    /// [`OTPDatabase::mut_element`]
     pub fn mark_modified(&mut self) {
         self.needs_modification = true;
     }
 
+    /// Forgets the changes made by the user, so they are not saved.
+    /// The usage statistics are discarded too, since saving them would also save the changes.
+    pub(crate) fn discard_modifications(&mut self) {
+        self.needs_modification = false;
+        self.silently_modified = false;
+    }
+
+    /// Returns the key derivation parameters used when the database is saved with a password
+    pub fn kdf(&self) -> KdfParams {
+        self.kdf
+    }
+
+    /// Sets the key derivation parameters used the next time the database is saved with a password
+    pub fn set_kdf(&mut self, kdf: KdfParams) {
+        self.kdf = kdf;
+    }
+
+    /// Returns the age recipients which can unlock the database
+    pub fn recipients(&self) -> &[String] {
+        &self.recipients
+    }
+
+    /// Adds an age recipient, returns false if it was already present
+    pub fn add_recipient(&mut self, recipient: String) -> bool {
+        if self.recipients.contains(&recipient) {
+            return false;
+        }
+        self.recipients.push(recipient);
+        self.mark_modified();
+        true
+    }
+
+    /// Removes an age recipient, returns false if it was not present
+    pub fn remove_recipient(&mut self, recipient: &str) -> bool {
+        let before = self.recipients.len();
+        self.recipients.retain(|r| r != recipient);
+        let removed = before != self.recipients.len();
+        if removed {
+            self.mark_modified();
+        }
+        removed
+    }
+
+    /// Records that the code at the given position has been copied or printed
+    pub(crate) fn record_use(&mut self, index: usize) {
+        if let Some(element) = self.elements.get_mut(index) {
+            element.last_used_at = Some(current_timestamp());
+            element.use_count = element.use_count.saturating_add(1);
+            self.silently_modified = true;
+        }
+    }
+
+    /// Permanently deletes the code at the given position, erasing it from the history too
     pub fn delete_element(&mut self, index: usize) {
         self.mark_modified();
-        self.elements.remove(index);
+        let element = self.elements.remove(index);
+        self.history.retain(|entry| entry.id != element.id);
     }
 
+    /// Returns all the codes, including the ones in the trash, in the order they are stored
     pub fn elements_ref(&self) -> &[OTPElement] {
         &self.elements
     }
 
+    /// Returns the code at the given position, if any
     pub fn get_element(&self, i: usize) -> Option<&OTPElement> {
         self.elements.get(i)
     }
 
+    /// Returns the code at the given position for editing, if any
     pub fn mut_element(&mut self, i: usize) -> Option<&mut OTPElement> {
         self.elements.get_mut(i)
     }
 
-    pub fn sort(&mut self) {
-        self.elements.sort_unstable_by(|c1, c2| {
-            c1.issuer
-                .to_ascii_lowercase()
-                .cmp(&c2.issuer.to_ascii_lowercase())
-        });
+    /// Returns all the tags in use, sorted and without duplicates
+    pub fn tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .elements
+            .iter()
+            .filter(|e| !e.is_archived())
+            .flat_map(|e| e.tags.iter().cloned())
+            .collect();
+        tags.sort_unstable_by_key(|t| t.to_lowercase());
+        tags.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        tags
+    }
+
+    /// Returns a copy of the database containing only the codes having all the given tags,
+    /// excluding the ones in the trash
+    pub fn filter_by_tags(&self, tags: &[String]) -> OTPDatabase {
+        OTPDatabase {
+            version: self.version,
+            elements: self
+                .elements
+                .iter()
+                .filter(|e| !e.is_archived() && e.has_tags(tags))
+                .cloned()
+                .collect(),
+            history: vec![],
+            trash_retention_days: self.trash_retention_days,
+            needs_modification: false,
+            kdf: self.kdf,
+            recipients: self.recipients.clone(),
+            silently_modified: false,
+        }
+    }
+
+    pub(crate) fn sort(&mut self) {
+        self.elements
+            .sort_by_cached_key(|e| e.issuer.to_ascii_lowercase());
+    }
+
+    /// Exchanges the positions of two codes in the order chosen by the user
+    pub(crate) fn swap_positions(&mut self, first: usize, second: usize) {
+        if first == second || first >= self.elements.len() || second >= self.elements.len() {
+            return;
+        }
+        let position = self.elements[first].position;
+        self.elements[first].position = self.elements[second].position;
+        self.elements[second].position = position;
+        self.mark_modified();
     }
 }
 
@@ -134,36 +340,175 @@ impl OTPDatabase {
     setter(into),
     build_fn(validate = "Self::validate", error = "ErrReport")
 )]
+#[builder_struct_attr(doc = "Builder of [`OTPElement`], checking the encoding of the secret")]
+/// A code stored in the database, with everything needed to generate it
 pub struct OTPElement {
+    /// Shared secret, base32 encoded except for the hex encoded mOTP secrets
     #[builder(setter(custom))]
     pub secret: String,
+    /// Service issuing the code
     pub issuer: String,
+    /// Account name, or the serial of vendor codes like Battle.net
     pub label: String,
+    /// Length of the generated codes
     #[builder(default = "6")]
     pub digits: u64,
+    /// Kind of code
     #[serde(rename = "type")]
     #[builder(setter(custom), default)]
     pub type_: OTPType,
+    /// Hash function used to generate the codes
     #[builder(default)]
     pub algorithm: OTPAlgorithm,
+    /// Seconds each time based code is valid for
     #[builder(default = "30")]
     pub period: u64,
+    /// Counter of HOTP codes and of the OCRA suites using one
     #[builder(setter(into), default)]
     pub counter: Option<u64>,
+    /// Pin of Yandex and mOTP codes, also hashed in the OCRA suites requiring it
     #[builder(setter(into), default)]
     pub pin: Option<String>,
+    /// Stable identifier of the code, nil until it is added to a database
+    #[serde(default)]
+    #[builder(default)]
+    #[zeroize(skip)]
+    pub id: Uuid,
+    /// User defined tags to group the codes
+    #[serde(default)]
+    #[builder(default)]
+    pub tags: Vec<String>,
+    /// Free text notes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub note: Option<String>,
+    /// Website of the service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub url: Option<String>,
+    /// Arbitrary user defined values, like account numbers or security questions
+    #[serde(default, skip_serializing_if = "CustomFields::is_empty")]
+    #[builder(default)]
+    pub fields: CustomFields,
+    /// Backup codes given by the service
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[builder(default)]
+    pub recovery_codes: Vec<RecoveryCode>,
+    /// Position of the code in the order chosen by the user
+    #[serde(default)]
+    #[builder(default)]
+    pub position: u64,
+    /// Favorite codes can be listed before the others
+    #[serde(default)]
+    #[builder(default)]
+    pub favorite: bool,
+    /// Unix timestamp of when the code has been added
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub created_at: Option<u64>,
+    /// Unix timestamp of when the code has been copied or printed the last time
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub last_used_at: Option<u64>,
+    /// Number of times the code has been copied or printed
+    #[serde(default)]
+    #[builder(default)]
+    pub use_count: u64,
+    /// Unix timestamp of when the code has been moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub archived_at: Option<u64>,
+    /// Seconds added to the clock when generating the time based codes, to match a drifting server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub time_offset_seconds: Option<i64>,
+    /// Unix timestamp the TOTP time steps are counted from (T0), the Unix epoch if missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(default)]
+    pub epoch: Option<u64>,
+    /// OCRA suite describing how the responses are computed, like OCRA-1:HOTP-SHA1-6:QN08
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into), default)]
+    pub ocra_suite: Option<String>,
+    /// How TOTP and HOTP codes are rendered, decimal digits by default
+    #[serde(default, skip_serializing_if = "CodeEncoder::is_decimal")]
+    #[builder(default)]
+    pub encoder: CodeEncoder,
+}
+
+/// Custom fields of a code, sorted by name.
+/// Needed because zeroize does not support `BTreeMap`.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Hash, Default)]
+#[serde(transparent)]
+pub struct CustomFields(BTreeMap<String, String>);
+
+impl CustomFields {
+    /// Checks if there are no custom fields
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Deref for CustomFields {
+    type Target = BTreeMap<String, String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
 }
 
-static ALLOWED_DIGITS_RANGE: std::ops::RangeInclusive<u64> = 1..=10;
+impl DerefMut for CustomFields {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<BTreeMap<String, String>> for CustomFields {
+    fn from(fields: BTreeMap<String, String>) -> Self {
+        Self(fields)
+    }
+}
+
+impl Zeroize for CustomFields {
+    fn zeroize(&mut self) {
+        std::mem::take(&mut self.0)
+            .into_iter()
+            .for_each(|(mut name, mut value)| {
+                name.zeroize();
+                value.zeroize();
+            });
+    }
+}
+
+pub(crate) static ALLOWED_DIGITS_RANGE: std::ops::RangeInclusive<u64> = 1..=10;
 
 impl OTPElement {
+    /// Returns the `otpauth://` URI of the code, used to enroll it in other apps
     pub fn get_otpauth_uri(&self) -> String {
-        let otp_type = self.type_.to_string().to_lowercase();
         let secret = &urlencoding::encode(self.secret.as_str());
         let label = get_label(&self.issuer, &self.label);
-        let algorithm = self.algorithm.to_string().to_uppercase();
-        let digits = self.digits;
-        let period = self.period;
+        // Battle.net and Symantec VIP codes are standard TOTP ones, which other apps understand
+        let (otp_type, algorithm, digits, period) = match self.type_ {
+            OTPType::Blizzard => (
+                "totp".to_string(),
+                OTPAlgorithm::Sha1,
+                BLIZZARD_DIGITS,
+                BLIZZARD_PERIOD,
+            ),
+            OTPType::Symantec => (
+                "totp".to_string(),
+                OTPAlgorithm::Sha1,
+                SYMANTEC_DIGITS,
+                SYMANTEC_PERIOD,
+            ),
+            _ => (
+                self.type_.to_string().to_lowercase(),
+                self.algorithm,
+                self.digits,
+                self.period,
+            ),
+        };
+        let algorithm = algorithm.to_string().to_uppercase();
         let mut uri: String = format!(
             "otpauth://{otp_type}/{label}?secret={secret}&algorithm={algorithm}&digits={digits}&period={period}&lock=false"
         );
@@ -172,9 +517,50 @@ impl OTPElement {
             uri.push_str("&counter=");
             uri.push_str(self.counter.unwrap_or(0).to_string().as_str());
         }
+        if self.type_ == OTPType::Ocra {
+            if let Some(suite) = &self.ocra_suite {
+                uri.push_str("&ocra_suite=");
+                uri.push_str(&urlencoding::encode(suite));
+            }
+            if let Some(counter) = self.counter {
+                uri.push_str("&counter=");
+                uri.push_str(counter.to_string().as_str());
+            }
+        }
+        if let Some(epoch) = self.epoch.filter(|e| *e > 0 && self.type_ == OTPType::Totp) {
+            uri.push_str("&t0=");
+            uri.push_str(epoch.to_string().as_str());
+        }
         uri
     }
 
+    /// Checks if the code is in the trash
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+
+    /// Checks if the code has all the given tags, ignoring case
+    pub fn has_tags(&self, tags: &[String]) -> bool {
+        tags.iter()
+            .all(|tag| self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim())))
+    }
+
+    /// Adds the given tags, skipping the ones already present
+    pub fn add_tags(&mut self, tags: &[String]) {
+        for tag in tags.iter().map(|t| t.trim()).filter(|t| !t.is_empty()) {
+            if !self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+                self.tags.push(tag.to_string());
+            }
+        }
+    }
+
+    /// Removes the given tags, ignoring case
+    pub fn remove_tags(&mut self, tags: &[String]) {
+        self.tags
+            .retain(|t| !tags.iter().any(|tag| t.eq_ignore_ascii_case(tag.trim())));
+    }
+
+    /// Renders the QR code of the `otpauth://` URI as text, to be printed in a terminal
     pub fn get_qrcode(&self) -> String {
         QrCode::new(self.get_otpauth_uri())
             .unwrap()
@@ -184,26 +570,34 @@ impl OTPElement {
             .build()
     }
 
+    /// Generates the current code
     pub fn get_otp_code(&self) -> Result<String, OtpError> {
+        self.get_otp_code_at(current_timestamp())
+    }
+
+    /// Generates the code for the given Unix timestamp, HOTP codes do not depend on it
+    pub fn get_otp_code_at(&self, time: u64) -> Result<String, OtpError> {
         if !ALLOWED_DIGITS_RANGE.contains(&self.digits) {
             return Err(OtpError::InvalidDigits);
         }
+        let time = self.corrected_time(time);
 
         match self.type_ {
             OTPType::Totp => {
-                let code = totp(&self.secret, self.algorithm, self.period)?;
+                let epoch = self.epoch.unwrap_or_default();
+                let code = totp(&self.secret, self.algorithm, self.period, epoch, time)?;
 
-                Ok(self.format_code(code)?)
+                self.encode_code(code)
             }
             OTPType::Hotp => match self.counter {
                 Some(counter) => {
                     let code = hotp(&self.secret, self.algorithm, counter)?;
 
-                    Ok(self.format_code(code)?)
+                    self.encode_code(code)
                 }
                 None => Err(OtpError::MissingCounter),
             },
-            OTPType::Steam => steam(&self.secret, self.algorithm, self.digits as usize),
+            OTPType::Steam => steam(&self.secret, self.algorithm, self.digits as usize, time),
             OTPType::Yandex => match &self.pin {
                 Some(pin) => yandex(
                     &self.secret,
@@ -211,6 +605,7 @@ impl OTPElement {
                     self.period,
                     self.digits as usize,
                     self.algorithm,
+                    time,
                 ),
                 None => Err(OtpError::MissingPin),
             },
@@ -220,14 +615,77 @@ impl OTPElement {
                     pin.as_str(),
                     self.period,
                     self.digits as usize,
+                    time,
                 )),
                 None => Err(OtpError::MissingPin),
             },
+            OTPType::Ocra => Err(OtpError::MissingChallenge),
+            OTPType::Blizzard => blizzard(&self.secret, time),
+            OTPType::Symantec => symantec(&self.secret, time),
+        }
+    }
+
+    /// Computes the OCRA response to the given challenge, using the stored counter and pin if
+    /// required by the suite. Session information is hex encoded.
+    pub fn ocra_response(
+        &self,
+        challenge: &str,
+        session: Option<&str>,
+        time: u64,
+    ) -> Result<String, OtpError> {
+        let suite = self.parsed_ocra_suite()?;
+        let input = OcraInput {
+            challenge,
+            counter: self.counter,
+            pin: self.pin.as_deref(),
+            session,
+            time: self.corrected_time(time),
+        };
+        ocra(&self.secret, &suite, &input)
+    }
+
+    /// Returns the Battle.net restore code, computed from the serial stored as label
+    pub fn blizzard_restore_code(&self) -> Result<String, OtpError> {
+        restore_code(&self.label, &self.secret)
+    }
+
+    pub(crate) fn parsed_ocra_suite(&self) -> Result<OcraSuite, OtpError> {
+        self.ocra_suite
+            .as_deref()
+            .ok_or(OtpError::InvalidOcraSuite("missing".to_string()))?
+            .parse()
+    }
+
+    /// Returns the seconds each code is valid for, missing for the codes which do not depend on time
+    pub fn time_step(&self) -> Option<u64> {
+        match self.type_ {
+            OTPType::Hotp | OTPType::Ocra => None,
+            OTPType::Steam => Some(STEAM_OTP_PERIOD),
+            OTPType::Blizzard => Some(BLIZZARD_PERIOD),
+            OTPType::Symantec => Some(SYMANTEC_PERIOD),
+            OTPType::Totp | OTPType::Yandex | OTPType::Motp => {
+                (self.period > 0).then_some(self.period)
+            }
         }
     }
 
-    fn format_code(&self, value: u32) -> Result<String, OtpError> {
-        format_code(self.digits, value)
+    /// Returns the seconds before the code generated at the given time changes
+    pub fn seconds_to_next_code(&self, time: u64) -> Option<u64> {
+        let mut time = self.corrected_time(time);
+        if self.type_ == OTPType::Totp {
+            time = time.saturating_sub(self.epoch.unwrap_or_default());
+        }
+        self.time_step().map(|step| step - time % step)
+    }
+
+    /// Applies the clock correction of this code to the given Unix timestamp
+    pub fn corrected_time(&self, time: u64) -> u64 {
+        time.saturating_add_signed(self.time_offset_seconds.unwrap_or_default())
+    }
+
+    /// Renders the generated value using the encoder of the code
+    pub(crate) fn encode_code(&self, value: u32) -> Result<String, OtpError> {
+        self.encoder.encode(value, self.digits)
     }
 }
 
@@ -272,16 +730,23 @@ impl OTPElementBuilder {
             return Err(eyre!("Secret must not be empty",));
         }
 
-        // Validate secret encoding
-        match self.type_.unwrap_or_default() {
-            OTPType::Motp => hex::decode(self.secret.as_ref().unwrap())
-                .map(|_| {})
-                .map_err(|e| eyre!("Invalid hex secret: {e}")),
-            _ => BASE32_NOPAD
-                .decode(self.secret.as_ref().unwrap().as_bytes())
-                .map(|_| {})
-                .map_err(|e| eyre!("Invalid BASE32 secret: {e}")),
-        }
+        validate_secret(
+            self.secret.as_ref().unwrap(),
+            self.type_.unwrap_or_default(),
+        )
+    }
+}
+
+/// Validate secret encoding
+pub(crate) fn validate_secret(secret: &str, otp_type: OTPType) -> Result<(), ErrReport> {
+    match otp_type {
+        OTPType::Motp => hex::decode(secret)
+            .map(|_| {})
+            .map_err(|e| eyre!("Invalid hex secret: {e}")),
+        _ => BASE32_NOPAD
+            .decode(secret.as_bytes())
+            .map(|_| {})
+            .map_err(|e| eyre!("Invalid BASE32 secret: {e}")),
     }
 }
 
@@ -293,9 +758,12 @@ fn get_label(issuer: &str, label: &str) -> String {
 
 #[cfg(test)]
 mod test {
+    use uuid::Uuid;
+
+    use crate::otp::code_encoder::CodeEncoder;
     use crate::otp::otp_element::OTPAlgorithm::Sha1;
     use crate::otp::otp_element::OTPType::Totp;
-    use crate::otp::otp_element::{OTPElement, OTPElementBuilder};
+    use crate::otp::otp_element::{CustomFields, OTPDatabase, OTPElement, OTPElementBuilder};
 
     use crate::otp::from_otp_uri::FromOtpUri;
     use crate::otp::otp_error::OtpError;
@@ -313,6 +781,22 @@ mod test {
             period: 30,
             counter: None,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         };
         assert_eq!(
             "otpauth://totp/IssuerText:LabelText?secret=xr5gh44x7bprcqgrdtulafeevt5rxqlbh5wvked22re43dh2d4mapv5g&algorithm=SHA1&digits=6&period=30&lock=false",
@@ -332,6 +816,22 @@ mod test {
             period: 30,
             counter: None,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         };
         assert_eq!(
             "otpauth://totp/:LabelText?secret=xr5gh44x7bprcqgrdtulafeevt5rxqlbh5wvked22re43dh2d4mapv5g&algorithm=SHA1&digits=6&period=30&lock=false",
@@ -351,6 +851,22 @@ mod test {
             period: 30,
             counter: None,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         };
         let otp_uri = "otpauth://totp/IssuerText:LabelText?secret=xr5gh44x7bprcqgrdtulafeevt5rxqlbh5wvked22re43dh2d4mapv5g&algorithm=SHA1&digits=6&period=30&lock=false";
 
@@ -378,6 +894,22 @@ mod test {
             period: 30,
             counter: None,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         };
 
         // Act
@@ -402,6 +934,22 @@ mod test {
             period: 30,
             counter: None,
             pin: None,
+            id: Uuid::nil(),
+            tags: vec![],
+            note: None,
+            url: None,
+            fields: CustomFields::default(),
+            recovery_codes: vec![],
+            position: 0,
+            favorite: false,
+            created_at: None,
+            last_used_at: None,
+            use_count: 0,
+            archived_at: None,
+            time_offset_seconds: None,
+            epoch: None,
+            ocra_suite: None,
+            encoder: CodeEncoder::Decimal,
         };
 
         // Act
@@ -494,4 +1042,235 @@ mod test {
         assert_eq!("foo", actual.issuer.as_str());
         assert_eq!("bar", actual.label.as_str());
     }
+
+    #[test]
+    fn test_tags_are_case_insensitive() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("AA")
+            .label("label")
+            .issuer("")
+            .build()
+            .unwrap();
+
+        // Act
+        element.add_tags(&["Work".to_string(), " work ".to_string(), "2FA".to_string()]);
+        element.remove_tags(&["2fa".to_string()]);
+
+        // Assert
+        assert_eq!(vec!["Work".to_string()], element.tags);
+        assert!(element.has_tags(&["WORK".to_string()]));
+        assert!(!element.has_tags(&["Work".to_string(), "2FA".to_string()]));
+    }
+
+    #[test]
+    fn test_note_url_and_fields_round_trip() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("AA")
+            .label("label")
+            .issuer("")
+            .note(Some("note".to_string()))
+            .url(Some("https://example.com".to_string()))
+            .build()
+            .unwrap();
+        element
+            .fields
+            .insert("Account".to_string(), "123".to_string());
+
+        // Act
+        let json = serde_json::to_string(&element).unwrap();
+        let deserialized: OTPElement = serde_json::from_str(&json).unwrap();
+
+        // Assert
+        assert_eq!(element, deserialized);
+        assert!(json.contains(r#""fields":{"Account":"123"}"#));
+    }
+
+    #[test]
+    fn test_code_generation_at_given_time() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+            .label("label")
+            .issuer("issuer")
+            .digits(8_u64)
+            .build()
+            .unwrap();
+
+        // Act
+        let code = element.get_otp_code_at(1111111109);
+        let next =
+            element.get_otp_code_at(1111111109 + element.seconds_to_next_code(1111111109).unwrap());
+
+        // Assert
+        assert_eq!(Ok("07081804".to_string()), code);
+        assert_eq!(Ok("14050471".to_string()), next);
+        assert_eq!(Some(1), element.seconds_to_next_code(1111111109));
+    }
+
+    #[test]
+    fn test_time_offset_shifts_the_clock() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+            .label("label")
+            .issuer("issuer")
+            .digits(8_u64)
+            .build()
+            .unwrap();
+        element.time_offset_seconds = Some(-30);
+
+        // Act
+        let code = element.get_otp_code_at(1111111139);
+        let verified = element.verify("07081804", 0, 1111111139);
+
+        // Assert
+        assert_eq!(Ok("07081804".to_string()), code);
+        assert_eq!(Ok(Some(0)), verified);
+        assert_eq!(Some(1), element.seconds_to_next_code(1111111139));
+    }
+
+    #[test]
+    fn test_otp_uri_epoch_round_trip() {
+        // Arrange
+        let otp_uri = "otpauth://totp/foo:bar?secret=JBSWY3DPEHPK3PXP&epoch=1000000000";
+
+        // Act
+        let element = OTPElement::from_otp_uri(otp_uri).unwrap();
+        let exported = element.get_otpauth_uri();
+
+        // Assert
+        assert_eq!(Some(1000000000), element.epoch);
+        assert!(exported.ends_with("&t0=1000000000"));
+        assert_eq!(
+            element,
+            OTPElement::from_otp_uri(exported.as_str()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_otp_uri_ocra_round_trip() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+            .label("label")
+            .issuer("issuer")
+            .type_(OTPType::Ocra)
+            .digits(8_u64)
+            .counter(Some(5))
+            .ocra_suite(Some("OCRA-1:HOTPC-SHA1-8:QN08".to_string()))
+            .build()
+            .unwrap();
+
+        // Act
+        let exported = element.get_otpauth_uri();
+        let imported = OTPElement::from_otp_uri(exported.as_str()).unwrap();
+
+        // Assert
+        assert!(exported.contains("&ocra_suite=OCRA-1%3AHOTPC-SHA1-8%3AQN08"));
+        assert_eq!(element.ocra_suite, imported.ocra_suite);
+        assert_eq!(element.counter, imported.counter);
+        assert_eq!(
+            element.ocra_response("12345678", None, 0),
+            imported.ocra_response("12345678", None, 0)
+        );
+    }
+
+    #[test]
+    fn test_otp_uri_exports_symantec_as_totp() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+            .label("SYMC12345678")
+            .issuer("Symantec")
+            .type_(OTPType::Symantec)
+            .build()
+            .unwrap();
+
+        // Act
+        let exported = element.get_otpauth_uri();
+        let imported = OTPElement::from_otp_uri(exported.as_str()).unwrap();
+
+        // Assert
+        assert!(exported.starts_with("otpauth://totp/"));
+        assert_eq!(OTPType::Totp, imported.type_);
+        assert_eq!(
+            element.get_otp_code_at(1111111109),
+            imported.get_otp_code_at(1111111109)
+        );
+    }
+
+    #[test]
+    fn test_otp_uri_exports_blizzard_as_totp() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+            .label("US-1234-5678-9012")
+            .issuer("Battle.net")
+            .type_(OTPType::Blizzard)
+            .build()
+            .unwrap();
+
+        // Act
+        let exported = element.get_otpauth_uri();
+        let imported = OTPElement::from_otp_uri(exported.as_str()).unwrap();
+
+        // Assert
+        assert!(exported.starts_with("otpauth://totp/"));
+        assert!(exported.contains("&algorithm=SHA1&digits=8&period=30"));
+        assert_eq!(
+            element.get_otp_code_at(1111111109),
+            imported.get_otp_code_at(1111111109)
+        );
+    }
+
+    #[test]
+    fn test_hotp_with_custom_alphabet() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ")
+            .label("label")
+            .issuer("issuer")
+            .type_(OTPType::Hotp)
+            .counter(0)
+            .encoder(CodeEncoder::Alphabet("0123456789".to_string()))
+            .build()
+            .unwrap();
+
+        // Act
+        // Matches the RFC 4226 code, like the decimal encoder
+        let code = element.get_otp_code();
+        let json = serde_json::to_string(&element).unwrap();
+
+        // Assert
+        assert_eq!(Ok("755224".to_string()), code);
+        assert_eq!(Ok(Some(0)), element.verify("755224", 0, 0));
+        assert_eq!(element, serde_json::from_str(&json).unwrap());
+    }
+
+    #[test]
+    fn test_discarded_changes_are_not_saved_with_the_usage_statistics() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("issuer")
+                .build()
+                .unwrap(),
+        );
+        database.discard_modifications();
+        database.mut_element(0).unwrap().issuer = "edited".to_string();
+        database.mark_modified();
+        database.record_use(0);
+
+        // Act
+        database.discard_modifications();
+
+        // Assert
+        assert!(!database.is_modified());
+        assert!(!database.needs_saving());
+    }
 }
@@ -1,15 +1,43 @@
 use data_encoding::DecodeKind;
 use std::fmt::{Display, Formatter};
 
+use super::{otp_algorithm::OTPAlgorithm, otp_type::OTPType};
+
+/// Errors occurring while generating or verifying the codes
 #[derive(Debug, PartialEq)]
 pub enum OtpError {
-    SecretEncoding(DecodeKind, usize), // Secret encoding error, of given kind at give position
-    MissingPin,                        // Missing Pin for Yandex / MOTP Codes
-    ShortSecret,                       // Short secret for Yandex codes
-    MissingCounter,                    // Missing counter for HOTP codes
-    InvalidOffset,                     // Invalid offset
-    InvalidDigest,                     // Invalid digest
-    InvalidDigits,                     // Invalid Digits value (too high or low)
+    /// Secret encoding error, of given kind at give position
+    SecretEncoding(DecodeKind, usize),
+    /// Missing Pin for Yandex / MOTP Codes
+    MissingPin,
+    /// Short secret for Yandex codes
+    ShortSecret,
+    /// Missing counter for HOTP codes
+    MissingCounter,
+    /// Invalid offset
+    InvalidOffset,
+    /// Invalid digest
+    InvalidDigest,
+    /// Invalid Digits value (too high or low)
+    InvalidDigits,
+    /// Algorithm which cannot be used with the code type
+    UnsupportedAlgorithm(OTPType, OTPAlgorithm),
+    /// Code type which cannot be verified
+    UnsupportedVerification(OTPType),
+    /// Period equal to zero or too large
+    InvalidPeriod,
+    /// Malformed OCRA suite, with the reason
+    InvalidOcraSuite(String),
+    /// Challenge or session information not matching the OCRA suite
+    InvalidOcraInput(String),
+    /// OCRA codes are only computed from a challenge
+    MissingChallenge,
+    /// Malformed Battle.net serial
+    InvalidSerial(String),
+    /// Malformed Symantec VIP credential ID
+    InvalidCredentialId(String),
+    /// Custom alphabet with less than two characters or repeated ones
+    InvalidAlphabet,
 }
 
 impl Display for OtpError {
@@ -24,6 +52,30 @@ impl Display for OtpError {
             OtpError::InvalidOffset => f.write_str("Invalid offset"),
             OtpError::ShortSecret => f.write_str("Secret length less than 16 bytes"),
             OtpError::InvalidDigits => f.write_str("Digits value too high or low"),
+            OtpError::UnsupportedAlgorithm(otp_type, algorithm) => {
+                f.write_str(format!("{algorithm} is not supported by {otp_type} codes").as_str())
+            }
+            OtpError::UnsupportedVerification(otp_type) => {
+                f.write_str(format!("{otp_type} codes cannot be verified").as_str())
+            }
+            OtpError::InvalidPeriod => f.write_str("Invalid period value"),
+            OtpError::InvalidOcraSuite(reason) => {
+                f.write_str(format!("Invalid OCRA suite {reason}").as_str())
+            }
+            OtpError::InvalidOcraInput(reason) => {
+                f.write_str(format!("Invalid OCRA input, {reason}").as_str())
+            }
+            OtpError::MissingChallenge => f.write_str("Requires a challenge"),
+            OtpError::InvalidSerial(serial) => f.write_str(
+                format!("Invalid serial {serial}, expected a format like US-1234-5678-9012")
+                    .as_str(),
+            ),
+            OtpError::InvalidAlphabet => f.write_str(
+                "Invalid encoder, expected decimal, steam or alphabet: followed by at least two different characters",
+            ),
+            OtpError::InvalidCredentialId(id) => f.write_str(
+                format!("Invalid credential ID {id}, expected a format like SYMC12345678").as_str(),
+            ),
         }
     }
 }
@@ -4,25 +4,45 @@ use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use zeroize::Zeroize;
 
+/// Kind of code, defining how it is generated
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, ValueEnum, Hash, Default)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OTPType {
+    /// Time based code, as defined by RFC 6238
     #[serde(alias = "totp")]
     #[serde(alias = "TOTP")]
     #[default]
     Totp,
+    /// Counter based code, as defined by RFC 4226
     #[serde(alias = "hotp")]
     #[serde(alias = "HOTP")]
     Hotp,
+    /// Steam Guard code
     #[serde(alias = "steam")]
     #[serde(alias = "STEAM")]
     Steam,
+    /// Yandex code, also requiring a pin
     #[serde(alias = "yandex")]
     #[serde(alias = "YANDEX")]
     Yandex,
+    /// Mobile-OTP code, also requiring a pin
     #[serde(alias = "motp")]
     #[serde(alias = "MOTP")]
     Motp,
+    /// Challenge-response code, as defined by RFC 6287
+    #[serde(alias = "ocra")]
+    #[serde(alias = "OCRA")]
+    Ocra,
+    /// Battle.net authenticator
+    #[serde(alias = "blizzard")]
+    #[serde(alias = "BLIZZARD")]
+    #[value(alias = "battlenet")]
+    Blizzard,
+    /// Symantec VIP Access credential
+    #[serde(alias = "symantec")]
+    #[serde(alias = "SYMANTEC")]
+    #[value(alias = "vip")]
+    Symantec,
 }
 
 impl fmt::Display for OTPType {
@@ -33,6 +53,9 @@ impl fmt::Display for OTPType {
             OTPType::Steam => "STEAM",
             OTPType::Yandex => "YANDEX",
             OTPType::Motp => "MOTP",
+            OTPType::Ocra => "OCRA",
+            OTPType::Blizzard => "BLIZZARD",
+            OTPType::Symantec => "SYMANTEC",
         };
         write!(f, "{to_string}")
     }
@@ -45,6 +68,9 @@ impl From<&str> for OTPType {
             "STEAM" => Self::Steam,
             "YANDEX" => Self::Yandex,
             "MOTP" => Self::Motp,
+            "OCRA" => Self::Ocra,
+            "BLIZZARD" | "BATTLENET" => Self::Blizzard,
+            "SYMANTEC" | "VIP" => Self::Symantec,
             _ => Self::Totp,
         }
     }
@@ -0,0 +1,118 @@
+use super::{
+    algorithms::{hotp_maker::hotp, totp_maker::generate_totp},
+    otp_element::OTPElement,
+    otp_error::OtpError,
+    otp_type::OTPType,
+};
+
+impl OTPElement {
+    /// Checks the given code against the TOTP time steps within ±window of the given time,
+    /// or against the current HOTP counter and the next window ones.
+    /// Returns the offset of the matching time step or counter, the nearest ones are tried first.
+    pub fn verify(&self, code: &str, window: u64, time: u64) -> Result<Option<i64>, OtpError> {
+        let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+        let window = window.min(i64::MAX as u64) as i64;
+        let time = self.corrected_time(time);
+
+        match self.type_ {
+            OTPType::Totp => {
+                let period = i64::try_from(self.period).map_err(|_| OtpError::InvalidPeriod)?;
+                if period == 0 {
+                    return Err(OtpError::InvalidPeriod);
+                }
+                for step in nearest_first(window) {
+                    let skew = step.saturating_mul(period);
+                    if time as i64 + skew < 0 {
+                        continue;
+                    }
+                    let value = generate_totp(
+                        &self.secret,
+                        self.algorithm,
+                        time,
+                        self.epoch.unwrap_or_default(),
+                        self.period,
+                        skew,
+                    )?;
+                    if self.encode_code(value)? == code {
+                        return Ok(Some(step));
+                    }
+                }
+                Ok(None)
+            }
+            OTPType::Hotp => {
+                let counter = self.counter.ok_or(OtpError::MissingCounter)?;
+                for step in 0..=window {
+                    let Some(counter) = counter.checked_add(step as u64) else {
+                        break;
+                    };
+                    let value = hotp(&self.secret, self.algorithm, counter)?;
+                    if self.encode_code(value)? == code {
+                        return Ok(Some(step));
+                    }
+                }
+                Ok(None)
+            }
+            _ => Err(OtpError::UnsupportedVerification(self.type_)),
+        }
+    }
+}
+
+/// Returns 0, -1, 1, -2, 2... up to ±window
+fn nearest_first(window: i64) -> impl Iterator<Item = i64> {
+    std::iter::once(0).chain((1..=window).flat_map(|step| [-step, step]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::{
+        otp_algorithm::OTPAlgorithm, otp_element::OTPElementBuilder, otp_type::OTPType,
+    };
+
+    // RFC 6238 test secret "12345678901234567890", whose code at 59 seconds is 94287082
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_totp_verification_window() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret(RFC_SECRET)
+            .label("label")
+            .issuer("issuer")
+            .digits(8_u64)
+            .algorithm(OTPAlgorithm::Sha1)
+            .build()
+            .unwrap();
+
+        // Act
+        let current = element.verify("9428 7082", 1, 59);
+        let previous = element.verify("94287082", 1, 89);
+        let outside = element.verify("94287082", 1, 120);
+
+        // Assert
+        assert_eq!(Ok(Some(0)), current);
+        assert_eq!(Ok(Some(-1)), previous);
+        assert_eq!(Ok(None), outside);
+    }
+
+    #[test]
+    fn test_hotp_look_ahead() {
+        // Arrange
+        let element = OTPElementBuilder::default()
+            .secret(RFC_SECRET)
+            .label("label")
+            .issuer("issuer")
+            .type_(OTPType::Hotp)
+            .counter(1)
+            .build()
+            .unwrap();
+
+        // Act
+        // RFC 4226 codes of the counters 0 and 3
+        let past = element.verify("755224", 5, 0);
+        let ahead = element.verify("969429", 5, 0);
+
+        // Assert
+        assert_eq!(Ok(None), past);
+        assert_eq!(Ok(Some(2)), ahead);
+    }
+}
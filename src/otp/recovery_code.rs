@@ -0,0 +1,133 @@
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use super::otp_element::OTPElement;
+
+/// One-time backup code given by the service when enabling two factor authentication
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug, Hash, Zeroize)]
+pub struct RecoveryCode {
+    /// The code, as given by the service
+    pub code: String,
+    /// Whether the code has already been used
+    #[serde(default)]
+    pub used: bool,
+}
+
+impl RecoveryCode {
+    /// Creates an unused recovery code, trimming the given one
+    pub fn new(code: &str) -> Self {
+        Self {
+            code: code.trim().to_string(),
+            used: false,
+        }
+    }
+
+    /// Compares the codes ignoring case, whitespaces and dashes, which are often
+    /// used only to improve readability
+    fn matches(&self, code: &str) -> bool {
+        normalize(&self.code) == normalize(code)
+    }
+}
+
+fn normalize(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+impl OTPElement {
+    /// Adds the given recovery codes, skipping the empty and the already present ones.
+    /// Returns the number of added codes.
+    pub fn add_recovery_codes<'a>(&mut self, codes: impl IntoIterator<Item = &'a str>) -> usize {
+        let mut added = 0;
+        for code in codes.into_iter().filter(|c| !c.trim().is_empty()) {
+            if !self.recovery_codes.iter().any(|r| r.matches(code)) {
+                self.recovery_codes.push(RecoveryCode::new(code));
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Marks the given recovery code as used, or the first unused one if missing.
+    /// Returns the used code.
+    pub fn use_recovery_code(&mut self, code: Option<&str>) -> Result<String, String> {
+        let recovery_code = match code {
+            Some(code) => self
+                .recovery_codes
+                .iter_mut()
+                .find(|r| r.matches(code))
+                .ok_or(format!("Recovery code {code} not found"))?,
+            None => self
+                .recovery_codes
+                .iter_mut()
+                .find(|r| !r.used)
+                .ok_or("No unused recovery codes left".to_string())?,
+        };
+        if recovery_code.used {
+            return Err(format!(
+                "Recovery code {} has already been used",
+                recovery_code.code
+            ));
+        }
+        recovery_code.used = true;
+        Ok(recovery_code.code.clone())
+    }
+
+    /// Returns the number of recovery codes which have not been used yet
+    pub fn unused_recovery_codes(&self) -> usize {
+        self.recovery_codes.iter().filter(|r| !r.used).count()
+    }
+
+    /// Checks if the user stored some recovery codes, but all of them have been used
+    pub fn has_exhausted_recovery_codes(&self) -> bool {
+        !self.recovery_codes.is_empty() && self.unused_recovery_codes() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::otp_element::OTPElementBuilder;
+
+    #[test]
+    fn test_duplicated_codes_are_skipped() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("AA")
+            .label("label")
+            .issuer("issuer")
+            .build()
+            .unwrap();
+
+        // Act
+        let added = element.add_recovery_codes(["abcd-efgh", " ABCD EFGH ", "", "1234"]);
+
+        // Assert
+        assert_eq!(2, added);
+        assert_eq!("abcd-efgh", element.recovery_codes[0].code);
+    }
+
+    #[test]
+    fn test_use_recovery_codes() {
+        // Arrange
+        let mut element = OTPElementBuilder::default()
+            .secret("AA")
+            .label("label")
+            .issuer("issuer")
+            .build()
+            .unwrap();
+        element.add_recovery_codes(["abcd-efgh", "1234"]);
+
+        // Act
+        let used = element.use_recovery_code(Some("ABCDEFGH"));
+        let next = element.use_recovery_code(None);
+
+        // Assert
+        assert_eq!(Ok("abcd-efgh".to_string()), used);
+        assert_eq!(Ok("1234".to_string()), next);
+        assert!(element.has_exhausted_recovery_codes());
+        assert!(element.use_recovery_code(Some("1234")).is_err());
+        assert!(element.use_recovery_code(None).is_err());
+    }
+}
@@ -0,0 +1,131 @@
+use std::cmp::Reverse;
+
+use clap::ValueEnum;
+
+use super::otp_element::OTPElement;
+
+/// Orders in which the codes can be shown
+#[derive(PartialEq, Eq, Debug, Clone, Copy, ValueEnum, Default)]
+pub enum SortOrder {
+    /// Alphabetical order of the issuers
+    Issuer,
+    /// Alphabetical order of the labels
+    Label,
+    /// Most recently used codes first, then the most recently added ones
+    Recent,
+    /// Order chosen by the user
+    #[default]
+    Custom,
+}
+
+/// Returns the positions in the database of the given codes in the requested order.
+/// Favorite codes can be moved before the other ones, keeping the requested order between them.
+pub fn sorted_indexes(
+    elements: &[OTPElement],
+    order: SortOrder,
+    favorites_first: bool,
+) -> Vec<usize> {
+    let mut indexes: Vec<usize> = (0..elements.len()).collect();
+    match order {
+        SortOrder::Issuer => indexes.sort_by_cached_key(|&i| elements[i].issuer.to_lowercase()),
+        SortOrder::Label => indexes.sort_by_cached_key(|&i| elements[i].label.to_lowercase()),
+        // Codes are appended to the database, so the last ones are the most recently added
+        SortOrder::Recent => {
+            indexes.reverse();
+            indexes.sort_by_key(|&i| Reverse(elements[i].last_used_at));
+        }
+        SortOrder::Custom => indexes.sort_by_key(|&i| elements[i].position),
+    }
+    if favorites_first {
+        indexes.sort_by_key(|&i| !elements[i].favorite);
+    }
+    indexes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::otp_element::OTPElementBuilder;
+
+    use super::{SortOrder, sorted_indexes};
+
+    #[test]
+    fn test_sort_orders() {
+        // Arrange
+        let elements = vec![
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("b")
+                .position(2_u64)
+                .last_used_at(Some(1000))
+                .build()
+                .unwrap(),
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("C")
+                .favorite(true)
+                .build()
+                .unwrap(),
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("a")
+                .position(1_u64)
+                .build()
+                .unwrap(),
+        ];
+
+        // Act
+        let issuer = sorted_indexes(&elements, SortOrder::Issuer, false);
+        let recent = sorted_indexes(&elements, SortOrder::Recent, false);
+        let custom = sorted_indexes(&elements, SortOrder::Custom, false);
+
+        // Assert
+        assert_eq!(vec![2, 0, 1], issuer);
+        assert_eq!(vec![0, 2, 1], recent);
+        assert_eq!(vec![1, 2, 0], custom);
+    }
+
+    #[test]
+    fn test_favorites_first_keeps_order() {
+        // Arrange
+        let elements = vec![
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("a")
+                .build()
+                .unwrap(),
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("b")
+                .position(1_u64)
+                .favorite(true)
+                .build()
+                .unwrap(),
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("c")
+                .position(2_u64)
+                .build()
+                .unwrap(),
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("d")
+                .position(3_u64)
+                .favorite(true)
+                .build()
+                .unwrap(),
+        ];
+
+        // Act
+        let indexes = sorted_indexes(&elements, SortOrder::Issuer, true);
+
+        // Assert
+        assert_eq!(vec![1, 3, 0, 2], indexes);
+    }
+}
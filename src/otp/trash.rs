@@ -0,0 +1,207 @@
+use crate::utils::current_timestamp;
+
+use super::{history::HistoryAction, otp_element::OTPDatabase};
+
+const SECONDS_PER_DAY: u64 = 86400;
+
+impl OTPDatabase {
+    /// Moves the code at the given position to the trash, hiding it without deleting it
+    pub fn archive_element(&mut self, index: usize) -> Result<(), String> {
+        match self.elements.get(index) {
+            None => return Err(format!("No element found at index {}", index + 1)),
+            Some(element) if element.is_archived() => {
+                return Err(format!("Code {} is already in the trash", index + 1));
+            }
+            Some(_) => {}
+        }
+        self.record_change(index, HistoryAction::Archive);
+        self.elements[index].archived_at = Some(current_timestamp());
+        self.mark_modified();
+        Ok(())
+    }
+
+    /// Moves the code at the given position back from the trash
+    pub fn restore_element(&mut self, index: usize) -> Result<(), String> {
+        match self.elements.get(index) {
+            Some(element) if element.is_archived() => {}
+            _ => return Err(format!("Code {} is not in the trash", index + 1)),
+        }
+        self.record_change(index, HistoryAction::Restore);
+        self.elements[index].archived_at = None;
+        self.mark_modified();
+        Ok(())
+    }
+
+    /// Days after which the codes in the trash are permanently deleted, never if None
+    pub fn trash_retention_days(&self) -> Option<u64> {
+        self.trash_retention_days
+    }
+
+    /// Sets the days after which the codes in the trash are permanently deleted, never if None
+    pub fn set_trash_retention_days(&mut self, days: Option<u64>) {
+        self.trash_retention_days = days;
+        self.mark_modified();
+    }
+
+    /// Returns the positions of the codes in the trash
+    pub fn archived_indexes(&self) -> Vec<usize> {
+        self.elements
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_archived())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Permanently deletes all the codes in the trash, returning how many were deleted
+    pub fn purge_trash(&mut self) -> usize {
+        self.purge_archived_before(u64::MAX)
+    }
+
+    /// Permanently deletes the codes which have been in the trash longer than the retention period,
+    /// if set. Returns how many codes were deleted.
+    pub(crate) fn purge_expired_trash(&mut self, now: u64) -> usize {
+        let Some(days) = self.trash_retention_days else {
+            return 0;
+        };
+        let modified = self.needs_modification;
+        let purged =
+            self.purge_archived_before(now.saturating_sub(days.saturating_mul(SECONDS_PER_DAY)));
+        // The automatic purge must not be reported as a change made by the user
+        if purged > 0 {
+            self.needs_modification = modified;
+            self.silently_modified = true;
+        }
+        purged
+    }
+
+    fn purge_archived_before(&mut self, timestamp: u64) -> usize {
+        let expired: Vec<usize> = self
+            .archived_indexes()
+            .into_iter()
+            .filter(|&i| self.elements[i].archived_at.is_some_and(|t| t <= timestamp))
+            .collect();
+        // Delete from the last one, so the other positions do not change
+        expired.iter().rev().for_each(|&i| self.delete_element(i));
+        expired.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::otp::{
+        history::HistoryAction,
+        otp_element::{OTPDatabase, OTPElementBuilder},
+    };
+
+    #[test]
+    fn test_archive_and_restore() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("first")
+                .build()
+                .unwrap(),
+        );
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("second")
+                .build()
+                .unwrap(),
+        );
+
+        // Act
+        database.archive_element(1).unwrap();
+        let archived = database.archived_indexes();
+        let archived_twice = database.archive_element(1);
+        database.restore_element(1).unwrap();
+
+        // Assert
+        assert_eq!(vec![1], archived);
+        assert!(archived_twice.is_err());
+        assert!(database.archived_indexes().is_empty());
+        assert!(database.restore_element(1).is_err());
+    }
+
+    #[test]
+    fn test_expired_codes_are_purged() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("first")
+                .build()
+                .unwrap(),
+        );
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("second")
+                .build()
+                .unwrap(),
+        );
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("third")
+                .build()
+                .unwrap(),
+        );
+        database.trash_retention_days = Some(30);
+        database.archive_element(0).unwrap();
+        database.archive_element(2).unwrap();
+        database.mut_element(0).unwrap().archived_at = Some(0);
+        let purged_id = database.elements_ref()[0].id;
+
+        // Act
+        let purged = database.purge_expired_trash(31 * 86400);
+
+        // Assert
+        assert_eq!(1, purged);
+        assert_eq!(2, database.elements_ref().len());
+        assert_eq!("second", database.elements_ref()[0].issuer);
+        assert_eq!(vec![1], database.archived_indexes());
+        assert!(database.history().iter().all(|e| e.id != purged_id));
+    }
+
+    #[test]
+    fn test_permanent_delete_erases_the_history() {
+        // Arrange
+        let mut database = OTPDatabase::default();
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("first")
+                .build()
+                .unwrap(),
+        );
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("AA")
+                .label("label")
+                .issuer("second")
+                .build()
+                .unwrap(),
+        );
+        database.record_change(0, HistoryAction::Edit);
+        let deleted_id = database.elements_ref()[0].id;
+
+        // Act
+        database.delete_element(0);
+
+        // Assert
+        assert_eq!(1, database.elements_ref().len());
+        assert_eq!(1, database.history().len());
+        assert!(database.history().iter().all(|e| e.id != deleted_id));
+    }
+}
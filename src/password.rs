@@ -0,0 +1,196 @@
+use std::io::{self, BufRead};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::{env, fs};
+
+use color_eyre::eyre::eyre;
+use zeroize::Zeroize;
+
+use crate::utils;
+
+/// Environment variable containing a file descriptor to read the password from
+pub const PASSWORD_FD_ENV: &str = "COTP_PASSWORD_FD";
+
+/// Where a password is read from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum PasswordSource {
+    /// Ask the user interactively
+    #[default]
+    Prompt,
+    /// First line of the standard input
+    Stdin,
+    /// First line of the given file
+    File(PathBuf),
+    /// First line of the standard output of the given shell command
+    Command(String),
+    /// First line read from the given file descriptor
+    FileDescriptor(u32),
+}
+
+impl PasswordSource {
+    /// Picks the first source given, falling back to the interactive prompt
+    pub(crate) fn from_options(file: Option<PathBuf>, command: Option<String>) -> Self {
+        match (file, command) {
+            (Some(file), _) => Self::File(file),
+            (None, Some(command)) => Self::Command(command),
+            (None, None) => Self::Prompt,
+        }
+    }
+
+    /// Reads the file descriptor from the `COTP_PASSWORD_FD` environment variable, if set
+    pub(crate) fn from_env() -> color_eyre::Result<Option<Self>> {
+        match env::var(PASSWORD_FD_ENV) {
+            Ok(fd) => fd
+                .trim()
+                .parse::<u32>()
+                .map(|fd| Some(Self::FileDescriptor(fd)))
+                .map_err(|_| eyre!("Invalid file descriptor in {PASSWORD_FD_ENV}: {fd}")),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Reads a password, prompting the given message if interactive.
+    /// The minimum length only applies to the prompt, so existing short passwords can still be passed by scripts.
+    pub(crate) fn read(&self, message: &str, minimum_length: usize) -> color_eyre::Result<String> {
+        match self {
+            Self::Prompt => Ok(utils::password(message, minimum_length)),
+            _ => self.read_non_interactive(),
+        }
+    }
+
+    /// Reads a new password, asking the user to type it twice if interactive
+    pub(crate) fn read_new(
+        &self,
+        message: &str,
+        minimum_length: usize,
+    ) -> color_eyre::Result<String> {
+        match self {
+            Self::Prompt => Ok(utils::verified_password(message, minimum_length)),
+            _ => {
+                let password = self.read_non_interactive()?;
+                if password.chars().count() < minimum_length {
+                    return Err(eyre!(
+                        "The password must contain at least {minimum_length} characters"
+                    ));
+                }
+                Ok(password)
+            }
+        }
+    }
+
+    fn read_non_interactive(&self) -> color_eyre::Result<String> {
+        let mut contents = match self {
+            Self::Prompt => unreachable!("Interactive prompts are handled by the caller"),
+            Self::Stdin => {
+                let mut line = String::new();
+                io::stdin().lock().read_line(&mut line)?;
+                line
+            }
+            Self::File(path) => fs::read_to_string(path)
+                .map_err(|e| eyre!("Cannot read password file {}: {e}", path.display()))?,
+            Self::Command(command) => run_password_command(command)?,
+            Self::FileDescriptor(fd) => read_file_descriptor(*fd)?,
+        };
+        let password = first_line(&contents).to_string();
+        contents.zeroize();
+
+        if password.is_empty() {
+            return Err(eyre!("Empty password"));
+        }
+        Ok(password)
+    }
+}
+
+/// Returns the first line without its line terminator
+fn first_line(contents: &str) -> &str {
+    contents.lines().next().unwrap_or_default()
+}
+
+fn run_password_command(command: &str) -> color_eyre::Result<String> {
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let output = Command::new(shell)
+        .arg(flag)
+        .arg(command)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| eyre!("Cannot run password command: {e}"))?;
+    if !output.status.success() {
+        return Err(eyre!("Password command failed with {}", output.status));
+    }
+    String::from_utf8(output.stdout).map_err(|_| eyre!("Password command returned invalid UTF-8"))
+}
+
+#[cfg(unix)]
+fn read_file_descriptor(fd: u32) -> color_eyre::Result<String> {
+    fs::read_to_string(format!("/dev/fd/{fd}"))
+        .map_err(|e| eyre!("Cannot read password from file descriptor {fd}: {e}"))
+}
+
+#[cfg(not(unix))]
+fn read_file_descriptor(_fd: u32) -> color_eyre::Result<String> {
+    Err(eyre!("{PASSWORD_FD_ENV} is not supported on this platform"))
+}
+
+#[cfg(test)]
+mod tests {
+    use assert_fs::{NamedTempFile, prelude::FileWriteStr};
+
+    use super::PasswordSource;
+
+    #[test]
+    fn test_password_file_reads_first_line() {
+        // Arrange
+        let file = NamedTempFile::new("password.txt").unwrap();
+        file.write_str("mysecretpassword\r\nignored\n").unwrap();
+
+        // Act
+        let password = PasswordSource::File(file.path().to_path_buf()).read("", 8);
+
+        // Assert
+        assert_eq!("mysecretpassword", password.unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_password_command() {
+        // Arrange
+        let source = PasswordSource::Command("echo mysecretpassword".to_string());
+
+        // Act
+        let password = source.read_new("", 8);
+
+        // Assert
+        assert_eq!("mysecretpassword", password.unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failing_password_command() {
+        assert!(
+            PasswordSource::Command("false".to_string())
+                .read("", 0)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_short_password_is_only_rejected_when_new() {
+        // Arrange
+        let file = NamedTempFile::new("password.txt").unwrap();
+        file.write_str("short\n").unwrap();
+        let source = PasswordSource::File(file.path().to_path_buf());
+
+        // Act
+        let existing = source.read("", 8);
+        let new = source.read_new("", 8);
+
+        // Assert
+        assert_eq!("short", existing.unwrap());
+        assert!(new.is_err());
+    }
+}
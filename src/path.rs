@@ -1,27 +1,145 @@
+use color_eyre::eyre::eyre;
 use dirs::{data_dir, home_dir};
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::sync::RwLock;
 use std::{env, fs};
 
-use crate::arguments::CotpArgs;
-
 const CURRENT_DB_PATH: &str = "./db.cotp";
-const XDG_PATH: &str = "cotp/db.cotp";
-const HOME_PATH: &str = ".cotp/db.cotp";
+const XDG_DIR: &str = "cotp";
+const HOME_DIR: &str = ".cotp";
+const DATABASE_FILE: &str = "db.cotp";
+const VAULTS_DIR: &str = "vaults";
+const VAULT_EXTENSION: &str = "cotp";
+const DEFAULT_VAULT_CONFIG: &str = "default_vault";
+
+/// Name of the vault stored in the default database path
+pub const DEFAULT_VAULT_NAME: &str = "default";
 
-pub static DATABASE_PATH: OnceLock<PathBuf> = OnceLock::new();
+static DATABASE_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
 
-/// Initialize singleton database path
-pub fn init_path(args: &CotpArgs) -> PathBuf {
+/// Initialize singleton database path, from the given path or vault name.
+/// Without any of them, `COTP_DB_PATH` or the default vault are used.
+pub fn select_database_path(
+    database_path: Option<&str>,
+    vault: Option<&str>,
+) -> color_eyre::Result<PathBuf> {
+    let path = match (database_path, vault) {
+        (Some(path), _) => PathBuf::from(path),
+        (None, Some(vault)) => existing_vault_path(vault)?,
+        (None, None) => match env::var("COTP_DB_PATH") {
+            Ok(path) => PathBuf::from(path),
+            Err(_) => match default_vault() {
+                Some(vault) => existing_vault_path(&vault)?,
+                None => get_default_db_path(),
+            },
+        },
+    };
+    set_database_path(path.clone());
+    Ok(path)
+}
+
+/// Returns the path of the database currently in use
+pub fn database_path() -> PathBuf {
     DATABASE_PATH
-        .get_or_init(|| {
-            args.database_path
-                .as_ref()
-                .map(String::from)
-                .or(env::var("COTP_DB_PATH").ok())
-                .map_or_else(get_default_db_path, PathBuf::from)
+        .read()
+        .unwrap()
+        .clone()
+        .expect("Database path not initialized")
+}
+
+pub fn set_database_path(path: PathBuf) {
+    *DATABASE_PATH.write().unwrap() = Some(path);
+}
+
+/// Returns the path of the given vault, without checking if it exists
+pub fn vault_path(name: &str) -> color_eyre::Result<PathBuf> {
+    if name == DEFAULT_VAULT_NAME {
+        return Ok(get_default_db_path());
+    }
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(eyre!(
+            "Invalid vault name {name}, only letters, numbers, '-' and '_' are allowed"
+        ));
+    }
+    Ok(vaults_dir().join(format!("{name}.{VAULT_EXTENSION}")))
+}
+
+/// Returns the path of the given vault, failing if it does not exist
+pub fn existing_vault_path(name: &str) -> color_eyre::Result<PathBuf> {
+    let path = vault_path(name)?;
+    if name != DEFAULT_VAULT_NAME && !path.exists() {
+        return Err(eyre!(
+            "Vault {name} does not exist, create it using \"cotp vault create {name}\""
+        ));
+    }
+    Ok(path)
+}
+
+/// Returns the name of the vault in use, if it is not the default one
+pub fn current_vault() -> Option<String> {
+    let path = database_path();
+    (path.parent() == Some(vaults_dir().as_path()))
+        .then(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .flatten()
+}
+
+/// Lists the available vaults, starting from the default one
+pub fn list_vaults() -> Vec<String> {
+    let mut vaults: Vec<String> = fs::read_dir(vaults_dir())
+        .map(|entries| {
+            entries
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|e| e == VAULT_EXTENSION))
+                .filter_map(|path| path.file_stem().map(|s| s.to_string_lossy().into_owned()))
+                .collect()
         })
-        .to_owned()
+        .unwrap_or_default();
+    vaults.sort_unstable();
+    vaults.insert(0, DEFAULT_VAULT_NAME.to_string());
+    vaults
+}
+
+/// Returns the vault configured to be opened when no one is specified
+pub fn default_vault() -> Option<String> {
+    fs::read_to_string(default_vault_config_path())
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty() && v != DEFAULT_VAULT_NAME)
+}
+
+pub fn set_default_vault(name: &str) -> color_eyre::Result<()> {
+    let config = default_vault_config_path();
+    if name == DEFAULT_VAULT_NAME {
+        if config.exists() {
+            fs::remove_file(config)?;
+        }
+        return Ok(());
+    }
+    fs::create_dir_all(config.parent().unwrap())?;
+    fs::write(config, name)?;
+    Ok(())
+}
+
+fn vaults_dir() -> PathBuf {
+    cotp_data_dir().join(VAULTS_DIR)
+}
+
+fn default_vault_config_path() -> PathBuf {
+    cotp_data_dir().join(DEFAULT_VAULT_CONFIG)
+}
+
+/// Vaults are kept in the data directory even when a portable database is in use,
+/// so they are not scattered in the current directory
+fn cotp_data_dir() -> PathBuf {
+    data_dir()
+        .map(|p| p.join(XDG_DIR))
+        .or_else(|| home_dir().map(|p| p.join(HOME_DIR)))
+        .unwrap_or_default()
 }
 
 // Pushing an absolute path to a PathBuf replaces the entire PathBuf: https://doc.rust-lang.org/std/path/struct.PathBuf.html#method.push
@@ -33,10 +151,10 @@ fn get_default_db_path() -> PathBuf {
         return portable_path;
     }
 
-    let home_path = home_dir().map(|path| path.join(HOME_PATH));
+    let home_path = home_dir().map(|path| path.join(HOME_DIR).join(DATABASE_FILE));
 
     data_dir()
-        .map(|p| p.join(XDG_PATH))
+        .map(|p| p.join(XDG_DIR).join(DATABASE_FILE))
         .inspect(|xdg| {
             if !xdg.exists()
                 && let Some(home) = &home_path
@@ -50,3 +168,30 @@ fn get_default_db_path() -> PathBuf {
         .or(home_path)
         .unwrap_or(portable_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{DEFAULT_VAULT_NAME, get_default_db_path, vault_path};
+
+    #[test]
+    fn test_default_vault_path() {
+        assert_eq!(
+            get_default_db_path(),
+            vault_path(DEFAULT_VAULT_NAME).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_vault_path() {
+        let path = vault_path("work_2-fa").unwrap();
+
+        assert_eq!("work_2-fa.cotp", path.file_name().unwrap());
+        assert_eq!("vaults", path.parent().unwrap().file_name().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_vault_name() {
+        assert!(vault_path("../escape").is_err());
+        assert!(vault_path("").is_err());
+    }
+}
@@ -1,63 +1,263 @@
 use crate::crypto;
-use crate::otp::otp_element::{OTPDatabase, OTPElement};
-use crate::path::DATABASE_PATH;
-use crate::utils;
+use crate::crypto::cryptography::DecryptedDatabase;
+use crate::crypto::encrypted_database::KdfParams;
+use crate::otp::otp_element::{
+    CURRENT_DATABASE_VERSION, ENCRYPTED_RECIPIENTS_VERSION, OTPDatabase, OTPElement,
+};
+use crate::password::PasswordSource;
+use crate::path::database_path;
+use crate::utils::current_timestamp;
 use color_eyre::eyre::{ErrReport, eyre};
 use std::fs::read_to_string;
-use std::io::{self, BufRead};
+use std::io;
+use std::path::Path;
 use zeroize::Zeroize;
 
+/// Unlocked database, with the key and salt required to save it again
 pub type ReadResult = (OTPDatabase, Vec<u8>, Vec<u8>);
 
-pub fn get_elements_from_input() -> color_eyre::Result<ReadResult> {
-    let pw = utils::password("Password: ", 8);
+/// Unlocks the database reading the password from the given source
+pub fn get_elements_from_source(source: &PasswordSource) -> color_eyre::Result<ReadResult> {
+    let pw = source.read("Password: ", 8)?;
     get_elements_with_password(pw)
 }
 
-pub fn get_elements_from_stdin() -> color_eyre::Result<ReadResult> {
-    if let Some(password) = io::stdin().lock().lines().next() {
-        return get_elements_with_password(password?);
-    }
-    Err(eyre!("Failure during stdin reading"))
-}
-
 fn get_elements_with_password(mut password: String) -> color_eyre::Result<ReadResult> {
     let (elements, key, salt) = read_from_file(&password)?;
     password.zeroize();
     Ok((elements, key, salt))
 }
 
-pub fn read_decrypted_text(password: &str) -> color_eyre::Result<(String, Vec<u8>, Vec<u8>)> {
-    let encrypted_contents =
-        read_to_string(DATABASE_PATH.get().unwrap()).map_err(ErrReport::from)?;
-    if encrypted_contents.is_empty() {
-        return match delete_db() {
-            Ok(()) => Err(eyre!(
-                "Your database file was empty, please restart to create a new one.",
-            )),
-            Err(_) => Err(eyre!(
-                "Your database file is empty, please remove it manually and restart.",
-            )),
-        };
+/// Unlocks the database using an age identity file matching one of its recipients
+pub fn get_elements_with_identity(identity_file: &Path) -> color_eyre::Result<ReadResult> {
+    let encrypted_contents = read_encrypted_text()?;
+    let decrypted =
+        crypto::cryptography::decrypt_string_with_identity(&encrypted_contents, identity_file)?;
+    into_read_result(decrypted, &database_path())
+}
+
+/// Decrypts the database in the given path, migrating it to the current version if needed.
+/// The returned key and salt are required to save it again with [`OTPDatabase::save_to`].
+pub fn open_database(path: &Path, password: &str) -> color_eyre::Result<ReadResult> {
+    let encrypted_contents = read_to_string(path)?;
+    let decrypted = crypto::cryptography::decrypt_string(&encrypted_contents, password)?;
+    into_read_result(decrypted, path)
+}
+
+/// Creates an empty database in the given path, replacing any file already there,
+/// and returns it with the key and salt required to save it again with [`OTPDatabase::save_to`].
+///
+/// ```
+/// use cotp::{KdfParams, OTPElementBuilder, create_database, open_database};
+///
+/// let path = std::env::temp_dir().join("cotp_create_database.cotp");
+/// let (mut database, key, salt) = create_database(&path, "password", KdfParams::default())?;
+/// database.add_element(
+///     OTPElementBuilder::default()
+///         .secret("JBSWY3DPEHPK3PXP")
+///         .issuer("issuer")
+///         .label("label")
+///         .build()?,
+/// );
+/// database.save_to(&path, &key, &salt)?;
+///
+/// let (database, _, _) = open_database(&path, "password")?;
+/// assert_eq!(1, database.elements_ref().len());
+/// # std::fs::remove_file(path)?;
+/// # Ok::<(), color_eyre::Report>(())
+/// ```
+pub fn create_database(
+    path: &Path,
+    password: &str,
+    kdf: KdfParams,
+) -> color_eyre::Result<ReadResult> {
+    let mut database = OTPDatabase::default();
+    database.set_kdf(kdf);
+    let (key, salt) = database.save_with_password(path, password)?;
+    Ok((database, key, salt.to_vec()))
+}
+
+fn read_encrypted_text() -> color_eyre::Result<String> {
+    check_empty_database()?;
+    read_to_string(database_path()).map_err(ErrReport::from)
+}
+
+fn check_empty_database() -> color_eyre::Result<()> {
+    if std::fs::metadata(database_path())?.len() > 0 {
+        return Ok(());
+    }
+    match delete_db() {
+        Ok(()) => Err(eyre!(
+            "Your database file was empty, please restart to create a new one.",
+        )),
+        Err(_) => Err(eyre!(
+            "Your database file is empty, please remove it manually and restart.",
+        )),
     }
-    //rust close files at the end of the function
-    crypto::cryptography::decrypt_string(&encrypted_contents, password)
 }
 
 pub fn read_from_file(password: &str) -> color_eyre::Result<ReadResult> {
-    match read_decrypted_text(password) {
-        Ok((mut contents, key, salt)) => {
-            let mut database: OTPDatabase = serde_json::from_str(&contents)
-                .or_else(|_| serde_json::from_str::<Vec<OTPElement>>(&contents).map(Into::into))
-                .map_err(ErrReport::from)?;
-            contents.zeroize();
-            database.sort();
-            Ok((database, key, salt))
-        }
-        Err(e) => Err(e),
+    check_empty_database()?;
+    open_database(&database_path(), password)
+}
+
+fn into_read_result(
+    mut decrypted: DecryptedDatabase,
+    path: &Path,
+) -> color_eyre::Result<ReadResult> {
+    let contents = &decrypted.plain_text;
+    let mut database: OTPDatabase = serde_json::from_str(contents)
+        .or_else(|_| serde_json::from_str::<Vec<OTPElement>>(contents).map(Into::into))
+        .map_err(ErrReport::from)?;
+    decrypted.plain_text.zeroize();
+    database.kdf = decrypted.kdf;
+    check_header_recipients(&mut database, &decrypted.header_recipients)?;
+    // Persist the migrated database immediately, so the changes it introduces (like the ids) are stable
+    if database.version < CURRENT_DATABASE_VERSION {
+        database.save_to(path, &decrypted.key, &decrypted.salt)?;
+    }
+    database.purge_expired_trash(current_timestamp());
+    Ok((database, decrypted.key, decrypted.salt))
+}
+
+/// The recipients listed in the header are not authenticated, so they must never be trusted:
+/// anyone able to write the file could add their own and receive the key on the next save.
+fn check_header_recipients(
+    database: &mut OTPDatabase,
+    header_recipients: &[String],
+) -> color_eyre::Result<()> {
+    if header_recipients.is_empty() {
+        return Ok(());
+    }
+    // Older versions only stored them in the header, they cannot be verified
+    if database.version < ENCRYPTED_RECIPIENTS_VERSION {
+        eprintln!(
+            "Warning: the age recipients of this database were not authenticated and have been removed, add them again using \"cotp recipient add\""
+        );
+        database.mark_modified();
+        return Ok(());
     }
+    if header_recipients != database.recipients() {
+        return Err(eyre!(
+            "The age recipients in the database header do not match the encrypted ones, the file may have been tampered with"
+        ));
+    }
+    Ok(())
 }
 
 fn delete_db() -> io::Result<()> {
-    std::fs::remove_file(DATABASE_PATH.get().unwrap())
+    std::fs::remove_file(database_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use age::x25519;
+    use assert_fs::NamedTempFile;
+
+    use crate::crypto::cryptography::{argon_derive_key, encrypt_string_with_key, gen_salt};
+    use crate::crypto::encrypted_database::KdfParams;
+    use crate::otp::otp_element::{CURRENT_DATABASE_VERSION, OTPDatabase, OTPElementBuilder};
+
+    use super::open_database;
+
+    #[test]
+    fn test_save_and_open_database_in_given_path() {
+        // Arrange
+        let file = NamedTempFile::new("db.cotp").unwrap();
+        let kdf = KdfParams {
+            mem_cost: 8192,
+            time_cost: 1,
+            lanes: 1,
+            ..Default::default()
+        };
+        let mut database = OTPDatabase::default();
+        database.set_kdf(kdf);
+        database.add_element(
+            OTPElementBuilder::default()
+                .secret("JBSWY3DPEHPK3PXP")
+                .label("label")
+                .issuer("issuer")
+                .build()
+                .unwrap(),
+        );
+        let salt = gen_salt().unwrap();
+        let key = argon_derive_key(b"pa$$w0rd", &salt, &kdf).unwrap();
+
+        // Act
+        database.save_to(file.path(), &key, &salt).unwrap();
+        let (opened, _, _) = open_database(file.path(), "pa$$w0rd").unwrap();
+
+        // Assert
+        assert_eq!(database.elements_ref(), opened.elements_ref());
+        assert_eq!(kdf, opened.kdf());
+        assert!(open_database(file.path(), "wrong password").is_err());
+    }
+
+    #[test]
+    fn test_older_databases_get_stable_ids() {
+        // Arrange
+        let file = NamedTempFile::new("db.cotp").unwrap();
+        let kdf = KdfParams {
+            mem_cost: 8192,
+            time_cost: 1,
+            lanes: 1,
+            ..Default::default()
+        };
+        // Version 1 databases were only a list of codes, without ids
+        let elements = vec![
+            OTPElementBuilder::default()
+                .secret("JBSWY3DPEHPK3PXP")
+                .label("label")
+                .issuer("issuer")
+                .build()
+                .unwrap(),
+        ];
+        let salt = gen_salt().unwrap();
+        let key = argon_derive_key(b"pa$$w0rd", &salt, &kdf).unwrap();
+        let encrypted =
+            encrypt_string_with_key(&serde_json::to_string(&elements).unwrap(), &key, &salt, kdf)
+                .unwrap();
+        fs::write(file.path(), serde_json::to_string(&encrypted).unwrap()).unwrap();
+
+        // Act
+        let (first, _, _) = open_database(file.path(), "pa$$w0rd").unwrap();
+        let (second, _, _) = open_database(file.path(), "pa$$w0rd").unwrap();
+
+        // Assert
+        assert_eq!(CURRENT_DATABASE_VERSION, first.version);
+        assert!(!first.elements_ref()[0].id.is_nil());
+        assert_eq!(first.elements_ref()[0].id, second.elements_ref()[0].id);
+    }
+
+    #[test]
+    fn test_recipients_added_to_the_header_are_rejected() {
+        // Arrange
+        let file = NamedTempFile::new("db.cotp").unwrap();
+        let kdf = KdfParams {
+            mem_cost: 8192,
+            time_cost: 1,
+            lanes: 1,
+            ..Default::default()
+        };
+        let mut database = OTPDatabase::default();
+        database.set_kdf(kdf);
+        database.add_recipient(x25519::Identity::generate().to_public().to_string());
+        let salt = gen_salt().unwrap();
+        let key = argon_derive_key(b"pa$$w0rd", &salt, &kdf).unwrap();
+        database.save_to(file.path(), &key, &salt).unwrap();
+
+        let mut header: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(file.path()).unwrap()).unwrap();
+        header["recipients"] =
+            serde_json::json!([x25519::Identity::generate().to_public().to_string()]);
+        fs::write(file.path(), header.to_string()).unwrap();
+
+        // Act
+        let result = open_database(file.path(), "pa$$w0rd");
+
+        // Assert
+        assert!(result.is_err());
+    }
 }
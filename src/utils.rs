@@ -1,17 +1,105 @@
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::path::DATABASE_PATH;
-
-pub fn init_app() -> Result<bool, ()> {
-    let db_path = DATABASE_PATH.get().unwrap(); // Safe to unwrap because we initialize
-    let db_dir = db_path.parent().unwrap();
-    if !db_dir.exists() {
-        if let Err(_e) = std::fs::create_dir_all(db_dir) {
-            return Err(());
-        }
-        return Ok(true);
+/// Returns the current Unix timestamp, in seconds
+pub fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Formats a Unix timestamp as a UTC date, like 2024-01-31
+pub fn format_date(timestamp: u64) -> String {
+    // Converts the days since the epoch into a civil date, see https://howardhinnant.github.io/date_algorithms.html
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// Formats a Unix timestamp as a UTC date and time, like 2024-01-31 18:30
+pub fn format_date_time(timestamp: u64) -> String {
+    let seconds_of_day = timestamp % 86400;
+    format!(
+        "{} {:02}:{:02}",
+        format_date(timestamp),
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60
+    )
+}
+
+/// Parses a Unix timestamp or an RFC 3339 date, like 2024-01-31T18:30:00Z or 2024-01-31T20:30:00+02:00
+pub fn parse_timestamp(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    if let Ok(timestamp) = value.parse::<u64>() {
+        return Ok(timestamp);
+    }
+    let invalid = || format!("{value} is neither a Unix timestamp nor an RFC 3339 date");
+    let number = |text: &str, range: std::ops::Range<usize>| {
+        text.get(range)
+            .filter(|n| n.chars().all(|c| c.is_ascii_digit()))
+            .and_then(|n| n.parse::<i64>().ok())
+            .ok_or_else(invalid)
+    };
+
+    let separators = [(4, b'-'), (7, b'-'), (13, b':'), (16, b':')];
+    if separators
+        .iter()
+        .any(|(i, c)| value.as_bytes().get(*i) != Some(c))
+        || !matches!(value.as_bytes().get(10), Some(b'T' | b't' | b' '))
+    {
+        return Err(invalid());
+    }
+    let (year, month, day) = (
+        number(value, 0..4)?,
+        number(value, 5..7)?,
+        number(value, 8..10)?,
+    );
+    let (hours, minutes, seconds) = (
+        number(value, 11..13)?,
+        number(value, 14..16)?,
+        number(value, 17..19)?,
+    );
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    // 60 is allowed for leap seconds
+    if seconds > 60 {
+        return Err(invalid());
     }
-    Ok(!db_path.exists())
+
+    // Skip the fractional seconds, then read the UTC offset
+    let rest = value[19..].trim_start_matches(|c: char| c == '.' || c.is_ascii_digit());
+    let offset = match rest.as_bytes().first() {
+        Some(b'Z' | b'z') if rest.len() == 1 => 0,
+        Some(sign @ (b'+' | b'-')) if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let offset = number(rest, 1..3)? * 3600 + number(rest, 4..6)? * 60;
+            if *sign == b'+' { offset } else { -offset }
+        }
+        _ => return Err(invalid()),
+    };
+
+    // Converts the civil date into days since the epoch, see https://howardhinnant.github.io/date_algorithms.html
+    let year = year - i64::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146097 + day_of_era - 719468;
+
+    let timestamp = days * 86400 + hours * 3600 + minutes * 60 + seconds - offset;
+    u64::try_from(timestamp).map_err(|_| format!("{value} is before the Unix epoch"))
 }
 
 pub fn millis_before_next_step() -> u64 {
@@ -47,3 +135,30 @@ pub fn verified_password(message: &str, minimum_length: usize) -> String {
         return password;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{format_date, format_date_time, parse_timestamp};
+
+    #[test]
+    fn test_format_date() {
+        assert_eq!("1970-01-01", format_date(0));
+        assert_eq!("2000-02-29", format_date(951_782_400));
+        assert_eq!("2024-12-31", format_date(1_735_689_599));
+        assert_eq!("2024-12-31 23:59", format_date_time(1_735_689_599));
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(Ok(1_111_111_109), parse_timestamp("1111111109"));
+        assert_eq!(Ok(0), parse_timestamp("1970-01-01T00:00:00Z"));
+        assert_eq!(Ok(951_782_400), parse_timestamp("2000-02-29T00:00:00.250Z"));
+        assert_eq!(
+            Ok(1_735_689_599),
+            parse_timestamp("2025-01-01T01:59:59+02:00")
+        );
+        assert!(parse_timestamp("2024-13-01T00:00:00Z").is_err());
+        assert!(parse_timestamp("1969-12-31T23:59:59Z").is_err());
+        assert!(parse_timestamp("yesterday").is_err());
+    }
+}
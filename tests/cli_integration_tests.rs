@@ -2,7 +2,7 @@
 mod cli_integration_test {
 
     use assert_cmd::cargo::cargo_bin_cmd;
-    use predicates::str::{is_empty, is_match, starts_with};
+    use predicates::str::{contains, is_empty, is_match, starts_with};
 
     #[test]
     fn test_version_subcommand() {
@@ -33,4 +33,152 @@ Usage: cotp [OPTIONS] [COMMAND]",
         ))
         .stderr(is_empty());
     }
+
+    #[test]
+    fn test_password_command() {
+        // Arrange / Act
+        let mut command = cargo_bin_cmd!("cotp");
+        let assertion = command
+            .arg("--password-command")
+            .arg("echo 12345678")
+            .arg("--database-path")
+            .arg("test_samples/cli_integration_test/empty_database")
+            .arg("list")
+            .arg("--json")
+            .assert();
+
+        // Assert
+        assertion
+            .success()
+            .stdout(starts_with("["))
+            .stderr(is_empty());
+    }
+
+    #[test]
+    fn test_init_subcommand() {
+        // Arrange
+        let dir = assert_fs::TempDir::new().unwrap();
+        let database = dir.path().join("db.cotp");
+
+        // Act
+        let mut command = cargo_bin_cmd!("cotp");
+        let assertion = command
+            .arg("--database-path")
+            .arg(&database)
+            .arg("init")
+            .arg("--password-stdin")
+            .arg("--kdf-memory")
+            .arg("8")
+            .write_stdin("12345678\n")
+            .assert();
+
+        // Assert
+        assertion
+            .success()
+            .stdout(starts_with("Database created"))
+            .stderr(is_empty());
+        assert!(database.exists());
+    }
+
+    #[test]
+    fn test_missing_database_fails_non_interactively() {
+        // Arrange
+        let dir = assert_fs::TempDir::new().unwrap();
+
+        // Act
+        let mut command = cargo_bin_cmd!("cotp");
+        let assertion = command
+            .arg("--password-stdin")
+            .arg("--database-path")
+            .arg(dir.path().join("db.cotp"))
+            .arg("list")
+            .write_stdin("12345678\n")
+            .assert();
+
+        // Assert
+        assertion
+            .failure()
+            .stdout(starts_with("Database not found"));
+    }
+
+    /// Runs cotp with a temporary data directory, so the vaults of the user are not touched
+    fn command_with_data_dir(data_dir: &assert_fs::TempDir) -> assert_cmd::Command {
+        let mut command = cargo_bin_cmd!("cotp");
+        command
+            .env("HOME", data_dir.path())
+            .env("XDG_DATA_HOME", data_dir.path())
+            .env_remove("COTP_DB_PATH");
+        command
+    }
+
+    #[test]
+    fn test_vault_subcommand() {
+        // Arrange
+        let data_dir = assert_fs::TempDir::new().unwrap();
+
+        // Act
+        let create = command_with_data_dir(&data_dir)
+            .args(["--password-stdin", "vault", "create", "work"])
+            .write_stdin("12345678\n")
+            .assert();
+        let set_default = command_with_data_dir(&data_dir)
+            .args(["vault", "default", "work"])
+            .assert();
+        let list = command_with_data_dir(&data_dir)
+            .args(["vault", "list"])
+            .assert();
+        let open = command_with_data_dir(&data_dir)
+            .args(["--password-stdin", "list", "--json"])
+            .write_stdin("12345678\n")
+            .assert();
+
+        // Assert
+        create.success().stdout(starts_with("Vault work created"));
+        set_default.success();
+        list.success().stdout("  default\n* work\n");
+        open.success().stdout(starts_with("[]"));
+    }
+
+    #[test]
+    fn test_init_new_vault() {
+        // Arrange
+        let data_dir = assert_fs::TempDir::new().unwrap();
+
+        // Act
+        let init = command_with_data_dir(&data_dir)
+            .args([
+                "--vault",
+                "work",
+                "init",
+                "--password-stdin",
+                "--kdf-memory",
+                "8",
+            ])
+            .write_stdin("12345678\n")
+            .assert();
+        let list = command_with_data_dir(&data_dir)
+            .args(["vault", "list"])
+            .assert();
+
+        // Assert
+        init.success().stdout(starts_with("Database created"));
+        list.success().stdout("* default\n  work\n");
+    }
+
+    #[test]
+    fn test_missing_vault() {
+        // Arrange
+        let data_dir = assert_fs::TempDir::new().unwrap();
+
+        // Act
+        let assertion = command_with_data_dir(&data_dir)
+            .args(["--password-stdin", "--vault", "missing", "list"])
+            .write_stdin("12345678\n")
+            .assert();
+
+        // Assert
+        assertion
+            .failure()
+            .stdout(contains("Vault missing does not exist"));
+    }
 }